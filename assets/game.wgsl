@@ -5,9 +5,7 @@
 struct GameData {
     bg_color: vec4<f32>,
     circle_count: u32,
-    spare1: u32,
-    spare2: u32,
-    spare3: u32,
+    arena_aspect: f32,
 }
 
 @group(0) @binding(0) var<uniform> view: View;
@@ -27,14 +25,21 @@ struct GameData {
 // https://iquilezles.org/articles/distfunctions2d/
 // https://iquilezles.org/articles/smin/
 
+// BlobKind, stored in pos_radius.w. Must match kind_id in render.rs.
+const KIND_BOMB: u32 = 1u;
+const KIND_ARMORED: u32 = 2u;
+const KIND_SPLITTER: u32 = 3u;
+const KIND_LEECH: u32 = 4u;
+
 struct BlobData {
     color: vec3<f32>,
     position: vec2<f32>,
     radius: f32,
+    kind: u32,
 }
 
-fn load_blob_pos_radius(index: u32) -> vec3<f32> {
-    return textureLoad(pos_radius_tex, vec2(index, 0), 0).xyz;
+fn load_blob_pos_radius(index: u32) -> vec4<f32> {
+    return textureLoad(pos_radius_tex, vec2(index, 0), 0);
 }
 
 fn load_blob_data(index: u32) -> BlobData {
@@ -46,6 +51,7 @@ fn load_blob_data(index: u32) -> BlobData {
     blob.color = cir_color.rgb;
     blob.position = cir_data.xy;
     blob.radius = cir_data.z;
+    blob.kind = u32(cir_data.w);
 
     return blob;
 }
@@ -54,6 +60,43 @@ fn sdCircle(p: vec2<f32>, r: f32) -> f32 {
     return length(p) - r;
 }
 
+fn sdBlob(p: vec2<f32>, radius: f32, kind: u32) -> f32 {
+    switch kind {
+        // Two lobes
+        case KIND_SPLITTER: {
+            let lobe = vec2(radius * 0.35, 0.0);
+            return min(sdCircle(p - lobe, radius * 0.7), sdCircle(p + lobe, radius * 0.7));
+        }
+        // Wobbly edge
+        case KIND_LEECH: {
+            let a = atan2(p.y, p.x);
+            return sdCircle(p, radius * (1.0 + 0.08 * sin(a * 7.0 + globals.time * 4.0)));
+        }
+        default: {
+            return sdCircle(p, radius);
+        }
+    }
+}
+
+fn kind_color(color: vec3<f32>, kind: u32) -> vec3<f32> {
+    switch kind {
+        // Pulses
+        case KIND_BOMB: {
+            return color * (1.3 + 0.3 * sin(globals.time * 10.0));
+        }
+        // Dull metal
+        case KIND_ARMORED: {
+            return mix(color, vec3(0.55, 0.6, 0.65), 0.6);
+        }
+        case KIND_LEECH: {
+            return color * vec3(1.3, 0.4, 0.5);
+        }
+        default: {
+            return color;
+        }
+    }
+}
+
 fn opSmoothUnion(d1: f32, d2: f32, k: f32) -> f32 {
     let h = clamp(0.5 + 0.5 * (d2 - d1) / k, 0.0, 1.0);
     return mix(d2, d1, h) - k * h * (1.0 - h);
@@ -75,7 +118,7 @@ fn map_height(p: vec2<f32>) -> f32 {
     var c1 = 0.0;
     for (var i = 0u; i < game.circle_count; i += 1u) {
         let pos_radius = load_blob_pos_radius(i);
-        var c2 = sdCircle(p - pos_radius.xy, pos_radius.z);
+        var c2 = sdBlob(p - pos_radius.xy, pos_radius.z, u32(pos_radius.w));
         var shape_k = max(blob.radius * 0.5, 0.001);
         c1 = opSmoothUnion(c1, c2, shape_k);
     }
@@ -93,7 +136,8 @@ fn map(p: vec2<f32>) -> vec4<f32> {
         if blob.radius < 0.15 && blob.radius > 0.1 {
             blob.color *= 1.0 + saturate(-(blob.radius - 0.15)) * 4.0;
         }
-        var new_shape = vec4(blob.color, sdCircle(p - blob.position, blob.radius));
+        blob.color = kind_color(blob.color, blob.kind);
+        var new_shape = vec4(blob.color, sdBlob(p - blob.position, blob.radius, blob.kind));
 
 
         var shape_k = max(blob.radius * 0.5, 0.001);
@@ -107,6 +151,7 @@ fn map(p: vec2<f32>) -> vec4<f32> {
     return shape;
 }
 
+// Picking in sdf.rs mirrors this, keep the two in sync.
 fn ready_to_click_map(p: vec2<f32>) -> vec4<f32> {
     let blob = load_blob_data(0);
     var shape = vec4(0.0,0.0,0.0,1.0);
@@ -132,7 +177,12 @@ fn fragment(vert: VertexOutput) -> @location(0) vec4<f32> {
     let fragcoord = vert.position.xy;
     let frag_size = 1.0 / resolution;
     let frag_uv = fragcoord / resolution;
-    var p = (2.0 * fragcoord - resolution.xy) / resolution.y;
+    // Letterbox the play field, must match ArenaAspect::scale in sim.rs.
+    let arena_scale = min(resolution.y, resolution.x / game.arena_aspect) * 0.5;
+    var p = (fragcoord - resolution.xy * 0.5) / arena_scale;
+    if any(abs(p) > vec2(game.arena_aspect, 1.0)) {
+        return vec4(0.0, 0.0, 0.0, 1.0);
+    }
 
     let ripple = textureSample(ripple_texture, ripple_sampler, frag_uv);
 
@@ -3,9 +3,16 @@
 #import bevy_sprite::mesh2d_vertex_output::VertexOutput
 #import bevy_render::view::View
 
+// Must match MAX_RIPPLE_PRESSES in render.rs
+const MAX_PRESSES: u32 = 8u;
+
 struct GameData {
+    // z: -1 to clear, 1 for hover ripples
     mouse_pos_dt: vec4<f32>,
+    // w: arena aspect
     blob_pos_hit: vec4<f32>,
+    // Mouse presses and touch starts, z: 1 if used
+    presses: array<vec4<f32>, MAX_PRESSES>,
 }
 
 @group(0) @binding(0) var<uniform> view: View;
@@ -22,15 +29,17 @@ fn fragment(
     let resolution = view.viewport.zw;
     let coord = vec2<i32>(vert.position.xy);
     let fragcoord = vert.position.xy;
-    let p = (2.0 * fragcoord - resolution.xy) / resolution.y;
+    // Same play field mapping as game.wgsl
+    let arena_scale = min(resolution.y, resolution.x / data.blob_pos_hit.w) * 0.5;
+    let p = (fragcoord - resolution.xy * 0.5) / arena_scale;
 
     let mouse_pos = (data.mouse_pos_dt.xy * 0.5 + 0.5) * resolution.xy;
-    let mouse_click = data.mouse_pos_dt.z;
+    let mouse_mode = data.mouse_pos_dt.z;
     let dt = data.mouse_pos_dt.w;
 
     // TODO use dt
 
-    if (mouse_click == -1.0) {
+    if (mouse_mode == -1.0) {
         return vec4<f32>(0.0);
     }
 
@@ -81,12 +90,20 @@ fn fragment(
         (p_up - p_down) / 2.0
     );
 
-    let dist = distance(fragcoord.xy, mouse_pos) / resolution.y;
-    if (mouse_click >= 1.0) {
-        if (dist <= 0.07) {
-            result.x += dist * 15.0;
+    for (var i = 0u; i < MAX_PRESSES; i += 1u) {
+        let press = data.presses[i];
+        if (press.z >= 1.0) {
+            let press_pos = (press.xy * 0.5 + 0.5) * resolution.xy;
+            let dist = distance(fragcoord.xy, press_pos) / resolution.y;
+            if (dist <= 0.07) {
+                result.x += dist * 15.0;
+            }
         }
-    } else {
+    }
+
+    // Hover
+    if (mouse_mode >= 1.0) {
+        let dist = distance(fragcoord.xy, mouse_pos) / resolution.y;
         if (dist <= 0.02) {
             result.x += dist * 0.5;
         }
@@ -0,0 +1,78 @@
+//! Run with `cargo run --release --example grid_benchmark`.
+
+use bevy::prelude::*;
+
+use rng::GameRng;
+use spatial::BlobGrid;
+
+// Shared with the game, which uses the parts this doesn't.
+#[allow(dead_code)]
+#[path = "../src/rng.rs"]
+mod rng;
+#[allow(dead_code)]
+#[path = "../src/sampling.rs"]
+mod sampling;
+#[allow(dead_code)]
+#[path = "../src/spatial.rs"]
+mod spatial;
+
+/// Times splash hit lookups through `BlobGrid` against scanning every blob, for growing numbers
+/// of blobs with four splashes per blob.
+fn main() {
+    const ROUNDS: u32 = 20;
+    // `ArenaAspect::default()`.
+    let aspect = 16.0 / 9.0;
+    for blob_count in [100, 1_000, 5_000, 20_000] {
+        let mut rng = GameRng::new(blob_count);
+        // Shrink blobs as their number grows so the field stays about as crowded as a real game.
+        let max_radius = 0.5 / (blob_count as f32).sqrt();
+        let blobs: Vec<(Entity, Vec2, f32)> = (0..blob_count)
+            .map(|i| {
+                let pos = rng.next_vec2_signed() * vec2(aspect, 1.0);
+                (
+                    Entity::from_raw(i),
+                    pos,
+                    max_radius * (0.2 + rng.next_f32() * 0.8),
+                )
+            })
+            .collect();
+        let splashes: Vec<Vec2> = (0..blob_count * 4)
+            .map(|_| rng.next_vec2_signed() * vec2(aspect, 1.0))
+            .collect();
+
+        let start = std::time::Instant::now();
+        let mut scan_hits = 0;
+        for _ in 0..ROUNDS {
+            for splash in &splashes {
+                scan_hits += blobs
+                    .iter()
+                    .any(|(_, pos, radius)| splash.distance(*pos) < *radius)
+                    as u32;
+            }
+        }
+        let scan = start.elapsed() / ROUNDS;
+
+        let mut grid = BlobGrid::default();
+        let start = std::time::Instant::now();
+        let mut grid_hits = 0;
+        for _ in 0..ROUNDS {
+            grid.rebuild(blobs.iter().copied());
+            for splash in &splashes {
+                grid_hits += grid.at_point(*splash).any(|entity| {
+                    let (_, pos, radius) = blobs[entity.index() as usize];
+                    splash.distance(pos) < radius
+                }) as u32;
+            }
+        }
+        let grid_time = start.elapsed() / ROUNDS;
+
+        assert_eq!(scan_hits, grid_hits, "grid and scan disagree");
+        println!(
+            "blobs {blob_count:>6} splashes {:>6}  scan {:>10.3?}  grid {:>10.3?}  hits {}",
+            splashes.len(),
+            scan,
+            grid_time,
+            grid_hits / ROUNDS
+        );
+    }
+}
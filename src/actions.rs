@@ -0,0 +1,275 @@
+//! Named actions and the keys, mouse buttons and gamepad buttons bound to them. Bindings are
+//! saved to local storage as RON and can be changed on the bindings screen.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::mode::GameMode;
+use crate::storage;
+
+const BINDINGS_KEY: &str = "bindings";
+
+pub struct GlorbyActionsPlugin;
+
+impl Plugin for GlorbyActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_action_map);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    /// Mouse bindings click at the mouse cursor, the rest at the virtual cursor.
+    Click,
+    CursorUp,
+    CursorDown,
+    CursorLeft,
+    CursorRight,
+    Pause,
+    Resume,
+    NewGame,
+    Mute,
+    ToggleSnap,
+    Bindings,
+    /// Picks the mode of the next game on the pause screen.
+    SelectMode(GameMode),
+}
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::Click,
+        Action::CursorUp,
+        Action::CursorDown,
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::Pause,
+        Action::Resume,
+        Action::NewGame,
+        Action::Mute,
+        Action::ToggleSnap,
+        Action::Bindings,
+        Action::SelectMode(GameMode::Endless),
+        Action::SelectMode(GameMode::Blitz),
+        Action::SelectMode(GameMode::Zen),
+        Action::SelectMode(GameMode::Daily),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Click => "CLICK",
+            Action::CursorUp => "CURSOR UP",
+            Action::CursorDown => "CURSOR DOWN",
+            Action::CursorLeft => "CURSOR LEFT",
+            Action::CursorRight => "CURSOR RIGHT",
+            Action::Pause => "PAUSE",
+            Action::Resume => "RESUME",
+            Action::NewGame => "NEW GAME",
+            Action::Mute => "MUTE",
+            Action::ToggleSnap => "CURSOR SNAP",
+            Action::Bindings => "BINDINGS",
+            Action::SelectMode(GameMode::Endless) => "ENDLESS MODE",
+            Action::SelectMode(GameMode::Blitz) => "BLITZ MODE",
+            Action::SelectMode(GameMode::Zen) => "ZEN MODE",
+            Action::SelectMode(GameMode::Daily) => "DAILY MODE",
+        }
+    }
+
+    fn default_bindings(self) -> Vec<Binding> {
+        use Binding::*;
+        match self {
+            // Not Space, that starts a new game and would count as a miss while playing.
+            Action::Click => vec![
+                Mouse(MouseButton::Left),
+                Key(KeyCode::Enter),
+                Gamepad(GamepadButton::South),
+            ],
+            Action::CursorUp => vec![Key(KeyCode::ArrowUp), Gamepad(GamepadButton::DPadUp)],
+            Action::CursorDown => vec![Key(KeyCode::ArrowDown), Gamepad(GamepadButton::DPadDown)],
+            Action::CursorLeft => vec![Key(KeyCode::ArrowLeft), Gamepad(GamepadButton::DPadLeft)],
+            Action::CursorRight => {
+                vec![Key(KeyCode::ArrowRight), Gamepad(GamepadButton::DPadRight)]
+            }
+            Action::Pause | Action::Resume => vec![
+                Key(KeyCode::KeyP),
+                Key(KeyCode::Escape),
+                Key(KeyCode::Tab),
+                Gamepad(GamepadButton::Start),
+            ],
+            Action::NewGame => vec![Key(KeyCode::Space), Gamepad(GamepadButton::South)],
+            Action::Mute => vec![Key(KeyCode::KeyM)],
+            Action::ToggleSnap => vec![Key(KeyCode::KeyC)],
+            Action::Bindings => vec![Key(KeyCode::KeyB)],
+            Action::SelectMode(mode) => match mode {
+                GameMode::Endless => vec![Key(KeyCode::Digit1)],
+                GameMode::Blitz => vec![Key(KeyCode::Digit2)],
+                GameMode::Zen => vec![Key(KeyCode::Digit3)],
+                GameMode::Daily => vec![Key(KeyCode::Digit4)],
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                let name = name
+                    .strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name);
+                write!(f, "{}", name.to_uppercase())
+            }
+            Binding::Mouse(button) => write!(f, "MOUSE {}", format!("{button:?}").to_uppercase()),
+            Binding::Gamepad(button) => write!(f, "PAD {}", format!("{button:?}").to_uppercase()),
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct ActionMap(BTreeMap<Action, Vec<Binding>>);
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        Self(
+            Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+        )
+    }
+}
+
+impl ActionMap {
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn is_bound(&self, action: Action, binding: Binding) -> bool {
+        self.bindings(action).contains(&binding)
+    }
+
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.0.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.0.entry(action).or_default().clear();
+    }
+
+    /// The bindings of `action` for prompts, like `P OR ESCAPE`.
+    pub fn describe(&self, action: Action) -> String {
+        let bindings = self.bindings(action);
+        if bindings.is_empty() {
+            return String::from("(UNBOUND)");
+        }
+        bindings
+            .iter()
+            .map(Binding::to_string)
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    }
+
+    pub fn save(&self) {
+        match ron::ser::to_string_pretty(self, default()) {
+            Ok(s) => storage::save(BINDINGS_KEY, &s),
+            Err(e) => warn!("Failed to serialize bindings: {e}"),
+        }
+    }
+}
+
+fn load_action_map(mut commands: Commands) {
+    let mut action_map = ActionMap::default();
+    if let Some(s) = storage::load(BINDINGS_KEY) {
+        match ron::from_str::<ActionMap>(&s) {
+            // Actions missing from the file keep their defaults.
+            Ok(loaded) => action_map.0.extend(loaded.0),
+            Err(e) => warn!("Ignoring unreadable bindings: {e}"),
+        }
+    }
+    commands.insert_resource(action_map);
+}
+
+/// Some key or button pressed this frame, for rebinding.
+pub fn any_just_pressed<'a>(
+    keys: &ButtonInput<KeyCode>,
+    mouse_buttons: &ButtonInput<MouseButton>,
+    mut gamepads: impl Iterator<Item = &'a Gamepad>,
+) -> Option<Binding> {
+    keys.get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            mouse_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        })
+        .or_else(|| {
+            gamepads.find_map(|g| {
+                g.get_just_pressed()
+                    .next()
+                    .map(|button| Binding::Gamepad(*button))
+            })
+        })
+}
+
+/// Reads actions through the `ActionMap` instead of checking keys and buttons directly.
+#[derive(SystemParam)]
+pub struct Actions<'w, 's> {
+    pub map: Res<'w, ActionMap>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse_buttons: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl Actions<'_, '_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.map
+            .bindings(action)
+            .iter()
+            .any(|binding| match *binding {
+                Binding::Key(key) => self.keys.pressed(key),
+                Binding::Mouse(button) => self.mouse_buttons.pressed(button),
+                Binding::Gamepad(button) => self.gamepads.iter().any(|g| g.pressed(button)),
+            })
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.map
+            .bindings(action)
+            .iter()
+            .any(|binding| self.binding_just_pressed(*binding))
+    }
+
+    /// Ignores mouse bindings, for actions the mouse does through window events.
+    pub fn just_pressed_without_mouse(&self, action: Action) -> bool {
+        self.map
+            .bindings(action)
+            .iter()
+            .filter(|binding| !matches!(binding, Binding::Mouse(_)))
+            .any(|binding| self.binding_just_pressed(*binding))
+    }
+
+    fn binding_just_pressed(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keys.just_pressed(key),
+            Binding::Mouse(button) => self.mouse_buttons.just_pressed(button),
+            Binding::Gamepad(button) => self.gamepads.iter().any(|g| g.just_pressed(button)),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::audio::{PlaybackMode, Volume};
+use bevy::prelude::*;
+
+use crate::actions::{Action, Actions};
+use crate::sim::{BlobClickResult, BlobMerge};
+
+pub struct GlorbyAudioPlugin;
+
+impl Plugin for GlorbyAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, play_theme)
+            .add_systems(Update, (play_click_sounds, play_merge_sounds, mute));
+    }
+}
+
+#[derive(Component)]
+struct GameAudio;
+
+fn play_theme(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        AudioPlayer::new(asset_server.load("blorb_theme.flac")),
+        GameAudio,
+        PlaybackSettings {
+            mode: PlaybackMode::Loop,
+            volume: Volume::Decibels(-3.0),
+            speed: 1.0,
+            paused: false,
+            muted: false,
+            spatial: false,
+            spatial_scale: None,
+        },
+    ));
+}
+
+fn play_click_sounds(
+    mut commands: Commands,
+    mut click_results: EventReader<BlobClickResult>,
+    asset_server: Res<AssetServer>,
+) {
+    for result in click_results.read() {
+        if result.hit {
+            commands.spawn((
+                AudioPlayer::new(asset_server.load("hit.flac")),
+                GameAudio,
+                PlaybackSettings {
+                    mode: PlaybackMode::Despawn,
+                    volume: Volume::Decibels(-19.0),
+                    speed: 1.0,
+                    paused: false,
+                    muted: false,
+                    spatial: false,
+                    spatial_scale: None,
+                },
+            ));
+        } else {
+            commands.spawn((
+                AudioPlayer::new(asset_server.load("missed.flac")),
+                GameAudio,
+                PlaybackSettings {
+                    mode: PlaybackMode::Despawn,
+                    volume: Volume::Decibels(-24.0),
+                    speed: 1.0,
+                    paused: false,
+                    muted: false,
+                    spatial: false,
+                    spatial_scale: None,
+                },
+            ));
+        }
+    }
+}
+
+fn play_merge_sounds(
+    mut commands: Commands,
+    mut merges: EventReader<BlobMerge>,
+    asset_server: Res<AssetServer>,
+) {
+    // One sound per frame is plenty when a pile of blobs merges at once.
+    if merges.read().last().is_some() {
+        commands.spawn((
+            AudioPlayer::new(asset_server.load("hit.flac")),
+            GameAudio,
+            PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::Decibels(-26.0),
+                speed: 0.5,
+                paused: false,
+                muted: false,
+                spatial: false,
+                spatial_scale: None,
+            },
+        ));
+    }
+}
+
+fn mute(actions: Actions, mut audio_controller: Query<&mut AudioSink, With<GameAudio>>) {
+    let Ok(mut sink) = audio_controller.single_mut() else {
+        return;
+    };
+
+    if actions.just_pressed(Action::Mute) {
+        sink.toggle_mute();
+    }
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+/// How far one click's chain reaction spread.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ChainStats {
+    /// Blobs hit, including the clicked one.
+    pub reached: u32,
+    /// Longest run of blobs in the chain, the clicked blob is depth 1.
+    pub depth: u32,
+    /// Seconds from the click until the last splash of the chain was gone.
+    pub duration: f32,
+}
+
+impl ChainStats {
+    pub fn is_better_than(&self, other: &ChainStats) -> bool {
+        (self.reached, self.depth) > (other.reached, other.depth)
+    }
+}
+
+/// Chains that still have splashes in flight, keyed by the id stored on their splashes.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Combos {
+    next_id: u32,
+    pub active: Vec<(u32, ChainStats)>,
+}
+
+impl Combos {
+    pub fn start(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.active.push((
+            id,
+            ChainStats {
+                reached: 1,
+                depth: 1,
+                duration: 0.0,
+            },
+        ));
+        id
+    }
+
+    /// Returns the chain as it is after the hit, `None` if it already ended.
+    pub fn hit(&mut self, id: u32, depth: u32) -> Option<ChainStats> {
+        let (_, stats) = self.active.iter_mut().find(|(i, _)| *i == id)?;
+        stats.reached += 1;
+        stats.depth = stats.depth.max(depth);
+        Some(*stats)
+    }
+
+    /// The biggest chain still going, drives the score multiplier.
+    pub fn current(&self) -> Option<ChainStats> {
+        self.active
+            .iter()
+            .map(|(_, stats)| *stats)
+            .reduce(|a, b| if b.is_better_than(&a) { b } else { a })
+    }
+}
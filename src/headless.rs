@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+
+use crate::mode::GameMode;
+use crate::replay::{GlorbyReplayPlugin, Replay};
+use crate::rng::{NextGameSeed, daily_seed};
+use crate::scoring::ScoringRules;
+use crate::sim::{GameState, GlorbySimPlugin, Score};
+use crate::storage;
+use crate::tuning::{GameTuning, TUNING_PATH};
+
+const MAX_GAME_SECONDS: f64 = 60.0 * 60.0;
+
+/// Runs `games` games back to back without a window, GPU or audio and prints the results.
+/// Game `i` uses `seed + i`, so a run can be repeated exactly by passing the same seed. Daily
+/// games start from today's seed.
+/// With a replay, plays it back once instead.
+pub fn run(games: u32, tick_rate: f64, mode: GameMode, seed: Option<u32>, replay: Option<Replay>) {
+    let tuning = load_tuning();
+    if let Some(replay) = replay {
+        let (mode, seed) = (replay.mode, replay.seed);
+        let result = run_game(tick_rate, mode, seed, &tuning, Some(replay));
+        println!("replay: {mode} seed {seed} {result}");
+        return;
+    }
+
+    let base_seed = seed.unwrap_or_else(|| {
+        if mode.is_daily() {
+            return daily_seed(storage::today());
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        now.subsec_nanos() ^ now.as_secs() as u32
+    });
+    let mut total_score = 0.0;
+    for game in 0..games {
+        let seed = base_seed.wrapping_add(game);
+        let result = run_game(tick_rate, mode, seed, &tuning, None);
+        total_score += result.composite;
+        println!("game {game}: {mode} seed {seed} {result}");
+    }
+    if games > 0 {
+        println!("mean score {:0.1}", total_score / games as f32);
+    }
+}
+
+/// Same file the game loads through the asset server, read directly since there is no
+/// `AssetPlugin` here.
+fn load_tuning() -> GameTuning {
+    let path = std::path::Path::new("assets").join(TUNING_PATH);
+    let Ok(bytes) = std::fs::read(&path) else {
+        return GameTuning::default();
+    };
+    GameTuning::from_ron(&bytes).unwrap_or_else(|e| {
+        eprintln!("Failed to parse {}: {e}", path.display());
+        std::process::exit(1);
+    })
+}
+
+fn headless_app(tick_rate: f64, replay: Option<Replay>) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        StatesPlugin,
+        GlorbySimPlugin { tick_rate },
+        GlorbyReplayPlugin {
+            record: None,
+            playback: replay,
+        },
+    ));
+    // Advance time by exactly one fixed timestep per update so every update runs one tick.
+    let timestep = app.world().resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+    app.finish();
+    app.cleanup();
+    app
+}
+
+struct GameResult {
+    score: Score,
+    composite: f32,
+    seconds: f64,
+}
+
+impl std::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "score {:0.1} raw {:0.1} hits {} misses {} best chain {} time {:0.1}s",
+            self.composite,
+            self.score.raw,
+            self.score.hits,
+            self.score.misses,
+            self.score.best_chain.reached,
+            self.seconds
+        )
+    }
+}
+
+fn run_game(
+    tick_rate: f64,
+    mode: GameMode,
+    seed: u32,
+    tuning: &GameTuning,
+    replay: Option<Replay>,
+) -> GameResult {
+    // The replay plugin starts its own game.
+    let start_game = replay.is_none();
+    let mut app = headless_app(tick_rate, replay);
+    app.insert_resource(NextGameSeed(Some(seed)))
+        .insert_resource(mode)
+        .insert_resource(tuning.clone());
+    if start_game {
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Start);
+    }
+
+    let timestep = app.world().resource::<Time<Fixed>>().timestep();
+    let max_ticks = (MAX_GAME_SECONDS / timestep.as_secs_f64()) as u64;
+    let mut ticks = 0;
+    // First update applies the state transition and spawns the blobs.
+    app.update();
+    while ticks < max_ticks {
+        app.update();
+        ticks += 1;
+        if *app.world().resource::<State<GameState>>().get() == GameState::GameOver {
+            break;
+        }
+    }
+
+    let score = *app.world().resource::<Score>();
+    GameResult {
+        score,
+        composite: score.composite(app.world().resource::<ScoringRules>()),
+        seconds: ticks as f64 * timestep.as_secs_f64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::replay::ReplayClick;
+    use crate::sim::{ArenaAspect, DEFAULT_TICK_RATE};
+
+    #[test]
+    fn same_seed_plays_the_same_game() {
+        let tuning = GameTuning::default();
+        let replay = Replay {
+            mode: GameMode::Endless,
+            seed: 1234,
+            timestep: Duration::from_secs_f64(1.0 / DEFAULT_TICK_RATE),
+            arena_aspect: *ArenaAspect::default(),
+            clicks: (0..200)
+                .map(|i| ReplayClick {
+                    tick: 10 + i * 7,
+                    pos: vec2(
+                        (i * 37 % 100) as f32 / 50.0 - 1.0,
+                        (i * 61 % 100) as f32 / 50.0 - 1.0,
+                    ),
+                })
+                .collect(),
+        };
+        let a = run_game(
+            DEFAULT_TICK_RATE,
+            replay.mode,
+            replay.seed,
+            &tuning,
+            Some(replay.clone()),
+        );
+        let b = run_game(
+            DEFAULT_TICK_RATE,
+            replay.mode,
+            replay.seed,
+            &tuning,
+            Some(replay),
+        );
+        assert!(a.score.hits + a.score.misses > 0);
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.seconds, b.seconds);
+    }
+}
@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::mode::GameMode;
+use crate::replay::ReplayPlayback;
+use crate::rng::{GameRng, daily_seed};
+use crate::scoring::ScoringRules;
+use crate::sim::{GameState, Score, spawn_blobs_init_game};
+use crate::storage;
+
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const MAX_DAILY_BESTS: usize = 30;
+const HIGH_SCORES_KEY: &str = "highscores";
+
+/// Keeps the best runs of each game mode in local storage. Runs are submitted when they end or
+/// are abandoned.
+pub struct GlorbyHighScorePlugin;
+
+impl Plugin for GlorbyHighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunSubmitted>()
+            .add_systems(Startup, load_high_scores)
+            .add_systems(
+                OnEnter(GameState::Start),
+                (
+                    submit_run.before(spawn_blobs_init_game),
+                    reset_run_submitted.after(spawn_blobs_init_game),
+                ),
+            )
+            .add_systems(OnEnter(GameState::GameOver), submit_run)
+            .add_systems(Last, submit_run.run_if(on_event::<AppExit>));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HighScoreEntry {
+    /// Entries saved before game modes existed are endless runs.
+    #[serde(default)]
+    pub mode: GameMode,
+    pub score: f32,
+    pub hits: u64,
+    pub misses: u64,
+    pub seconds: f32,
+    pub seed: u32,
+    pub date: String,
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+    /// Best daily challenge run of each day, most recent day first.
+    #[serde(default)]
+    pub daily: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    /// Best first.
+    pub fn for_mode(&self, mode: GameMode) -> impl Iterator<Item = &HighScoreEntry> {
+        self.entries.iter().filter(move |e| e.mode == mode)
+    }
+
+    /// `date` as `YYYY-MM-DD`.
+    pub fn daily_best(&self, date: &str) -> Option<&HighScoreEntry> {
+        self.daily.iter().find(|e| e.date == date)
+    }
+
+    /// Returns true if the entry is the new best for its day.
+    pub fn insert_daily(&mut self, entry: HighScoreEntry) -> bool {
+        match self.daily.iter_mut().find(|e| e.date == entry.date) {
+            Some(best) if best.score >= entry.score => return false,
+            Some(best) => *best = entry,
+            None => self.daily.push(entry),
+        }
+        self.daily.sort_by(|a, b| b.date.cmp(&a.date));
+        self.daily.truncate(MAX_DAILY_BESTS);
+        true
+    }
+
+    /// Returns the 0 based rank within its game mode if the entry made it onto the table.
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        let rank = self
+            .for_mode(entry.mode)
+            .filter(|e| e.score >= entry.score)
+            .count();
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        let index = self
+            .entries
+            .iter()
+            .position(|e| entry.score > e.score)
+            .unwrap_or(self.entries.len());
+        let mode = entry.mode;
+        self.entries.insert(index, entry);
+        if let Some(index) = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.mode == mode)
+            .nth(MAX_HIGH_SCORES)
+            .map(|(i, _)| i)
+        {
+            self.entries.remove(index);
+        }
+        Some(rank)
+    }
+}
+
+#[derive(Resource, Clone, Copy, Default, Deref, DerefMut)]
+struct RunSubmitted(bool);
+
+fn load_high_scores(mut commands: Commands) {
+    let high_scores = storage::load(HIGH_SCORES_KEY)
+        .and_then(|s| match ron::from_str::<HighScores>(&s) {
+            Ok(high_scores) => Some(high_scores),
+            Err(e) => {
+                warn!("Ignoring unreadable high scores: {e}");
+                None
+            }
+        })
+        .unwrap_or_default();
+    commands.insert_resource(high_scores);
+}
+
+fn reset_run_submitted(mut submitted: ResMut<RunSubmitted>) {
+    **submitted = false;
+}
+
+fn submit_run(
+    mut submitted: ResMut<RunSubmitted>,
+    high_scores: Option<ResMut<HighScores>>,
+    score: Res<Score>,
+    rules: Res<ScoringRules>,
+    rng: Res<GameRng>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    let Some(mut high_scores) = high_scores else {
+        return;
+    };
+    if **submitted || score.time <= 0.0 || !score.mode.is_scored() || playback.is_some() {
+        return;
+    }
+    **submitted = true;
+
+    let entry = HighScoreEntry {
+        mode: score.mode,
+        score: score.composite(&rules),
+        hits: score.hits,
+        misses: score.misses,
+        seconds: score.time,
+        seed: rng.seed(),
+        date: storage::date_string(score.date),
+    };
+    // A `--seed` run in daily mode is on some other board, it doesn't count for the day.
+    let daily_best = score.mode.is_daily()
+        && entry.seed == daily_seed(score.date)
+        && high_scores.insert_daily(entry.clone());
+    if high_scores.insert(entry).is_some() || daily_best {
+        match ron::ser::to_string_pretty(&*high_scores, default()) {
+            Ok(s) => storage::save(HIGH_SCORES_KEY, &s),
+            Err(e) => warn!("Failed to serialize high scores: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mode: GameMode, score: f32) -> HighScoreEntry {
+        HighScoreEntry {
+            mode,
+            score,
+            hits: 0,
+            misses: 0,
+            seconds: 0.0,
+            seed: 0,
+            date: "2024-02-29".into(),
+        }
+    }
+
+    fn scores(high_scores: &HighScores, mode: GameMode) -> Vec<f32> {
+        high_scores.for_mode(mode).map(|e| e.score).collect()
+    }
+
+    #[test]
+    fn insert_keeps_each_mode_sorted() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.insert(entry(GameMode::Endless, 10.0)), Some(0));
+        assert_eq!(high_scores.insert(entry(GameMode::Blitz, 50.0)), Some(0));
+        assert_eq!(high_scores.insert(entry(GameMode::Endless, 30.0)), Some(0));
+        assert_eq!(high_scores.insert(entry(GameMode::Endless, 20.0)), Some(1));
+        assert_eq!(scores(&high_scores, GameMode::Endless), [30.0, 20.0, 10.0]);
+        assert_eq!(scores(&high_scores, GameMode::Blitz), [50.0]);
+    }
+
+    #[test]
+    fn table_is_capped_per_mode() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(entry(GameMode::Blitz, 1.0));
+        for score in 1..=MAX_HIGH_SCORES {
+            high_scores.insert(entry(GameMode::Endless, score as f32));
+        }
+        // Too low, and ties with the worst entry don't make it either.
+        assert_eq!(high_scores.insert(entry(GameMode::Endless, 0.5)), None);
+        assert_eq!(high_scores.insert(entry(GameMode::Endless, 1.0)), None);
+
+        assert_eq!(high_scores.insert(entry(GameMode::Endless, 5.5)), Some(5));
+        let endless = scores(&high_scores, GameMode::Endless);
+        assert_eq!(endless.len(), MAX_HIGH_SCORES);
+        assert_eq!(endless.last(), Some(&2.0));
+        assert_eq!(scores(&high_scores, GameMode::Blitz), [1.0]);
+    }
+}
@@ -0,0 +1,246 @@
+use bevy::input::ButtonState;
+use bevy::input::touch::TouchPhase;
+use bevy::prelude::*;
+use bevy::window::WindowEvent;
+use serde::{Deserialize, Serialize};
+
+use bevy::app::RunFixedMainLoopSystem;
+
+use crate::actions::{Action, ActionMap, Actions, Binding};
+use crate::replay::ReplayPlayback;
+use crate::sim::{ArenaAspect, BlobCanBeClicked, BlobClick, BlobPosition, GameState};
+use crate::storage;
+
+/// Play field units per second at full stick or arrow key deflection.
+pub const VIRTUAL_CURSOR_SPEED: f32 = 1.2;
+/// How far from the virtual cursor a click snaps to a clickable blob.
+pub const SNAP_RADIUS: f32 = 0.3;
+const CURSOR_SETTINGS_KEY: &str = "cursor";
+
+pub struct GlorbyInputPlugin;
+
+impl Plugin for GlorbyInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MousePosition>()
+            .init_resource::<PointerPresses>()
+            .add_systems(Startup, (load_cursor_settings, spawn_virtual_cursor))
+            .add_systems(
+                RunFixedMainLoop,
+                (handle_pointer_events, move_virtual_cursor)
+                    .chain()
+                    .in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop)
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(Update, draw_virtual_cursor);
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+pub struct MousePosition {
+    /// Play field position, see `ArenaAspect`.
+    pub arena: Vec2,
+    /// Window position in [-1, 1], y down.
+    pub ndc: Vec2,
+    /// What moved the cursor last.
+    pub source: PointerSource,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointerSource {
+    #[default]
+    Mouse,
+    /// Touch devices get no hover feedback.
+    Touch,
+    /// Gamepad stick or arrow keys, drawn by `draw_virtual_cursor`.
+    Virtual,
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CursorSettings {
+    /// Virtual cursor clicks snap to the nearest clickable blob within `SNAP_RADIUS`.
+    pub snap: bool,
+}
+
+impl CursorSettings {
+    pub fn save(&self) {
+        match ron::to_string(self) {
+            Ok(s) => storage::save(CURSOR_SETTINGS_KEY, &s),
+            Err(e) => warn!("Failed to serialize cursor settings: {e}"),
+        }
+    }
+}
+
+/// Window positions in [-1, 1] of this frame's mouse presses and touch starts, for ripples.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct PointerPresses(pub Vec<Vec2>);
+
+/// Reads cursor moves, button presses and touches in the order they happened, so every press is a
+/// click at the cursor position at that moment, even with several presses and moves in one frame.
+/// Every touch start is a click at its own position, so several fingers work at once.
+fn handle_pointer_events(
+    mut window_events: EventReader<WindowEvent>,
+    mut mouse_position: ResMut<MousePosition>,
+    mut presses: ResMut<PointerPresses>,
+    mut clicks: EventWriter<BlobClick>,
+    window: Single<&Window>,
+    arena_aspect: Res<ArenaAspect>,
+    action_map: Res<ActionMap>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    let window_size = window.resolution.size();
+    presses.0.clear();
+    for event in window_events.read() {
+        match event {
+            WindowEvent::CursorMoved(cursor_event) => {
+                mouse_position.ndc = cursor_event.position / window_size * 2.0 - 1.0;
+                mouse_position.arena =
+                    arena_aspect.window_to_arena(cursor_event.position, window_size);
+                mouse_position.source = PointerSource::Mouse;
+            }
+            WindowEvent::MouseButtonInput(button_event)
+                if button_event.state == ButtonState::Pressed
+                    && action_map.is_bound(Action::Click, Binding::Mouse(button_event.button)) =>
+            {
+                presses.0.push(mouse_position.ndc);
+                // Presses in the bars around the arena still ripple but aren't clicks, so they
+                // don't count as misses.
+                if playback.is_none() && arena_aspect.contains(mouse_position.arena) {
+                    clicks.write(BlobClick(mouse_position.arena));
+                }
+            }
+            WindowEvent::TouchInput(touch) if touch.phase == TouchPhase::Started => {
+                mouse_position.source = PointerSource::Touch;
+                presses.0.push(touch.position / window_size * 2.0 - 1.0);
+                let arena_pos = arena_aspect.window_to_arena(touch.position, window_size);
+                if playback.is_none() && arena_aspect.contains(arena_pos) {
+                    clicks.write(BlobClick(arena_pos));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Moves the cursor with the cursor actions or the gamepad stick, and clicks with the key and
+/// gamepad bindings of `Action::Click`. Feeds the same `MousePosition` and click path as the mouse.
+fn move_virtual_cursor(
+    actions: Actions,
+    gamepads: Query<&Gamepad>,
+    time: Res<Time>,
+    settings: Res<CursorSettings>,
+    mut mouse_position: ResMut<MousePosition>,
+    mut presses: ResMut<PointerPresses>,
+    mut clicks: EventWriter<BlobClick>,
+    clickable: Query<&BlobPosition, With<BlobCanBeClicked>>,
+    window: Single<&Window>,
+    arena_aspect: Res<ArenaAspect>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    const DIRECTIONS: [(Action, Vec2); 4] = [
+        (Action::CursorLeft, Vec2::NEG_X),
+        (Action::CursorRight, Vec2::X),
+        (Action::CursorUp, Vec2::NEG_Y),
+        (Action::CursorDown, Vec2::Y),
+    ];
+    let mut direction = Vec2::ZERO;
+    for (action, action_direction) in DIRECTIONS {
+        if actions.pressed(action) {
+            direction += action_direction;
+        }
+    }
+    for gamepad in &gamepads {
+        // Play field y is down.
+        direction += gamepad.left_stick() * vec2(1.0, -1.0);
+    }
+    let clicked = actions.just_pressed_without_mouse(Action::Click);
+    let direction = direction.clamp_length_max(1.0);
+    if direction == Vec2::ZERO && !clicked {
+        return;
+    }
+
+    let half_extent = vec2(**arena_aspect, 1.0);
+    let mut pos = (mouse_position.arena + direction * VIRTUAL_CURSOR_SPEED * time.delta_secs())
+        .clamp(-half_extent, half_extent);
+    if clicked
+        && settings.snap
+        && let Some(target) = nearest_within(pos, SNAP_RADIUS, clickable.iter().map(|p| **p))
+    {
+        pos = target;
+    }
+
+    let window_size = window.resolution.size();
+    mouse_position.arena = pos;
+    mouse_position.ndc = arena_aspect.arena_to_window(pos, window_size) / window_size * 2.0 - 1.0;
+    mouse_position.source = PointerSource::Virtual;
+    if clicked {
+        presses.0.push(mouse_position.ndc);
+        if playback.is_none() {
+            clicks.write(BlobClick(pos));
+        }
+    }
+}
+
+/// The closest of `positions` no further than `radius` from `pos`.
+pub fn nearest_within(
+    pos: Vec2,
+    radius: f32,
+    positions: impl Iterator<Item = Vec2>,
+) -> Option<Vec2> {
+    positions
+        .map(|p| (p, p.distance_squared(pos)))
+        .filter(|(_, d)| *d <= radius * radius)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(p, _)| p)
+}
+
+fn load_cursor_settings(mut commands: Commands) {
+    let settings = storage::load(CURSOR_SETTINGS_KEY)
+        .and_then(|s| match ron::from_str::<CursorSettings>(&s) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                warn!("Ignoring unreadable cursor settings: {e}");
+                None
+            }
+        })
+        .unwrap_or_default();
+    commands.insert_resource(settings);
+}
+
+const VIRTUAL_CURSOR_SIZE: f32 = 18.0;
+
+#[derive(Component)]
+struct VirtualCursorMarker;
+
+fn spawn_virtual_cursor(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Px(VIRTUAL_CURSOR_SIZE),
+            height: Val::Px(VIRTUAL_CURSOR_SIZE),
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BorderColor(Color::WHITE),
+        BorderRadius::MAX,
+        Visibility::Hidden,
+        VirtualCursorMarker,
+    ));
+}
+
+fn draw_virtual_cursor(
+    cursor: Single<(&mut Node, &mut Visibility), With<VirtualCursorMarker>>,
+    mouse_position: Res<MousePosition>,
+    window: Single<&Window>,
+    arena_aspect: Res<ArenaAspect>,
+    state: Res<State<GameState>>,
+) {
+    let (mut node, mut visibility) = cursor.into_inner();
+    if mouse_position.source != PointerSource::Virtual || *state.get() != GameState::Running {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Inherited;
+    let pos = arena_aspect.arena_to_window(mouse_position.arena, window.resolution.size());
+    node.left = Val::Px(pos.x - VIRTUAL_CURSOR_SIZE * 0.5);
+    node.top = Val::Px(pos.y - VIRTUAL_CURSOR_SIZE * 0.5);
+}
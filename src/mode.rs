@@ -0,0 +1,116 @@
+use std::fmt;
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const BLITZ_SECONDS: f32 = 90.0;
+
+/// Rules for the next game, picked from the pause menu. The mode of the game in progress is
+/// kept in `Score::mode`.
+#[derive(
+    Resource,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum GameMode {
+    /// Play until every blob is gone.
+    #[default]
+    Endless,
+    /// Score as much as possible before the clock runs out, only hits count.
+    Blitz,
+    /// Constant speed, blobs that shrink away come back at the edges, no score and no game over.
+    Zen,
+    /// Endless on the same board for everyone, seeded from the UTC date.
+    Daily,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Endless,
+        GameMode::Blitz,
+        GameMode::Zen,
+        GameMode::Daily,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Endless => "ENDLESS",
+            GameMode::Blitz => "BLITZ",
+            GameMode::Zen => "ZEN",
+            GameMode::Daily => "DAILY",
+        }
+    }
+
+    /// Seconds until the game ends on its own.
+    pub fn time_limit(self) -> Option<f32> {
+        match self {
+            GameMode::Endless | GameMode::Zen | GameMode::Daily => None,
+            GameMode::Blitz => Some(BLITZ_SECONDS),
+        }
+    }
+
+    pub fn speed_ramps(self) -> bool {
+        self != GameMode::Zen
+    }
+
+    /// Blobs that shrink to nothing come back instead of ending the game.
+    pub fn respawns_blobs(self) -> bool {
+        self == GameMode::Zen
+    }
+
+    /// Whether `WaveTuning` waves join the game.
+    pub fn has_waves(self) -> bool {
+        self != GameMode::Zen
+    }
+
+    /// Whether the score is shown and kept in the high score table.
+    pub fn is_scored(self) -> bool {
+        self != GameMode::Zen
+    }
+
+    /// Games get `rng::daily_seed` instead of a fresh seed.
+    pub fn is_daily(self) -> bool {
+        self == GameMode::Daily
+    }
+
+    /// Stable id for the replay format.
+    pub fn id(self) -> u8 {
+        match self {
+            GameMode::Endless => 0,
+            GameMode::Blitz => 1,
+            GameMode::Zen => 2,
+            GameMode::Daily => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<GameMode> {
+        GameMode::ALL.into_iter().find(|mode| mode.id() == id)
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name().to_lowercase())
+    }
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GameMode::ALL
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown game mode {s}"))
+    }
+}
@@ -0,0 +1,324 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::prelude::*;
+use bevy::render::render_resource::{
+    AsBindGroup, Extent3d, ShaderRef, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureUsages,
+};
+use bevy::render::view::RenderLayers;
+use bevy::sprite::{Material2d, Material2dPlugin};
+use bytemuck::cast_slice;
+
+use crate::input::{MousePosition, PointerPresses, PointerSource};
+use crate::sim::{
+    ArenaAspect, BlobCanBeClicked, BlobColor, BlobKind, BlobPosition, BlobPreviousPosition,
+    BlobSizeRadius, BlobWallHit, GameState, Score,
+};
+
+pub struct GlorbyRenderPlugin;
+
+impl Plugin for GlorbyRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            Material2dPlugin::<GameMaterial>::default(),
+            Material2dPlugin::<RippleMaterial>::default(),
+        ))
+        .add_systems(Startup, setup)
+        .add_systems(Update, ripple_swap.run_if(in_state(GameState::Running)))
+        .add_systems(Update, render_blobs);
+    }
+}
+
+fn render_blobs(
+    blobs: Query<(
+        &BlobSizeRadius,
+        &BlobPosition,
+        &BlobPreviousPosition,
+        &BlobColor,
+        Has<BlobCanBeClicked>,
+        Option<&BlobKind>,
+    )>,
+    fixed_time: Res<Time<Fixed>>,
+    arena_aspect: Res<ArenaAspect>,
+    mut game_materials: ResMut<Assets<GameMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let temp_click_color = Vec3::ONE * 2.0;
+    let alpha = fixed_time.overstep_fraction();
+
+    let (_, game_material) = game_materials.iter_mut().next().unwrap();
+    let mut temp_pos_radius = vec![];
+    let mut temp_color = vec![];
+
+    for (size, pos, prev_pos, color, can_be_clicked, kind) in blobs {
+        if !can_be_clicked {
+            let pos = prev_pos.lerp(**pos, alpha);
+            temp_pos_radius.push(pos.extend(**size).extend(kind_id(kind)));
+            temp_color.push(color.extend(0.0));
+        }
+    }
+
+    for (size, pos, prev_pos, _color, can_be_clicked, kind) in blobs {
+        if can_be_clicked {
+            let pos = prev_pos.lerp(**pos, alpha);
+            temp_pos_radius.push(pos.extend(**size).extend(kind_id(kind)));
+            temp_color.push(temp_click_color.extend(0.0));
+        }
+    }
+
+    if temp_pos_radius.is_empty() || temp_color.is_empty() {
+        temp_pos_radius.push(Default::default());
+        temp_color.push(Default::default());
+    }
+
+    game_material.pos_radius_tex = images.add(data_image(&temp_pos_radius));
+    game_material.color_tex = images.add(data_image(&temp_color));
+    game_material.circle_count = temp_pos_radius.len() as u32;
+    game_material.arena_aspect = **arena_aspect;
+}
+
+/// Must match the `KIND_` constants in game.wgsl.
+fn kind_id(kind: Option<&BlobKind>) -> f32 {
+    match kind {
+        None | Some(BlobKind::Normal) => 0.0,
+        Some(BlobKind::Bomb) => 1.0,
+        Some(BlobKind::Armored) => 2.0,
+        Some(BlobKind::Splitter) => 3.0,
+        Some(BlobKind::Leech) => 4.0,
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<GameMaterial>>,
+    mut ripple_materials: ResMut<Assets<RippleMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    asset_server: Res<AssetServer>,
+) {
+    let ripple_images = RippleImages::new(vec2(1280.0, 720.0), &mut images);
+
+    commands.spawn((
+        Msaa::Off,
+        Camera2d,
+        Camera {
+            hdr: true,
+            target: ripple_images.a.clone().into(),
+            ..default()
+        },
+        RenderLayers::layer(1),
+        RippleCamera,
+    ));
+
+    commands.spawn((
+        Mesh2d(meshes.add(fullscreen_tri())),
+        MeshMaterial2d(ripple_materials.add(RippleMaterial {
+            mouse_pos_dt: Vec4::ZERO,
+            blob_pos_hit: Vec4::ZERO,
+            presses: [Vec4::ZERO; MAX_RIPPLE_PRESSES],
+            prev_tex: ripple_images.b.clone(),
+        })),
+        Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
+        RenderLayers::layer(1),
+    ));
+
+    commands.spawn((
+        Msaa::Off,
+        Camera2d,
+        Camera {
+            hdr: true,
+            ..default()
+        },
+        Tonemapping::TonyMcMapface,
+    ));
+    let temp_pos_radius = vec![Vec4::ZERO];
+    let temp_color = vec![Vec4::ZERO];
+    commands.spawn((
+        Mesh2d(meshes.add(fullscreen_tri())),
+        MeshMaterial2d(materials.add(GameMaterial {
+            bg_color: vec4(1.0, 0.0, 1.0, 1.0),
+            circle_count: temp_pos_radius.len() as u32,
+            arena_aspect: ArenaAspect::default().0,
+            pos_radius_tex: images.add(data_image(&temp_pos_radius)),
+            color_tex: images.add(data_image(&temp_color)),
+            bg_tex: asset_server.load("sky.jpg"),
+            ripple_tex: ripple_images.a.clone(),
+        })),
+        Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
+    ));
+
+    commands.insert_resource(ripple_images);
+}
+
+fn ripple_swap(
+    presses: Res<PointerPresses>,
+    mut wall_hits: EventReader<BlobWallHit>,
+    mut ripple_images: ResMut<RippleImages>,
+    mut camera: Single<&mut Camera, With<RippleCamera>>,
+    window: Single<&Window>,
+    time: Res<Time>,
+    mut images: ResMut<Assets<Image>>,
+    mut ripple_materials: ResMut<Assets<RippleMaterial>>,
+    mut game_materials: ResMut<Assets<GameMaterial>>,
+    mouse_position: Res<MousePosition>,
+    score: Res<Score>,
+    arena_aspect: Res<ArenaAspect>,
+) {
+    let mut init = score.raw < 10.0;
+    ripple_images.swap();
+    let res = window.resolution.physical_size().as_vec2();
+    if ripple_images.res != res {
+        *ripple_images = RippleImages::new(res, &mut images);
+        init = true;
+    }
+    camera.target = ripple_images.a.clone().into();
+    let (_, ripple_material) = ripple_materials.iter_mut().next().unwrap();
+    ripple_material.mouse_pos_dt = vec4(
+        mouse_position.ndc.x,
+        mouse_position.ndc.y,
+        if init && presses.0.is_empty() {
+            -1.0
+        } else if mouse_position.source != PointerSource::Touch {
+            1.0
+        } else {
+            0.0
+        },
+        time.delta_secs(),
+    );
+    ripple_material.blob_pos_hit = wall_hits
+        .read()
+        .last()
+        .map_or(Vec3::ZERO, |hit| hit.0)
+        .extend(**arena_aspect);
+    ripple_material.presses = [Vec4::ZERO; MAX_RIPPLE_PRESSES];
+    for (slot, press) in ripple_material.presses.iter_mut().zip(&presses.0) {
+        *slot = press.extend(1.0).extend(0.0);
+    }
+    ripple_material.prev_tex = ripple_images.b.clone();
+
+    let (_, game_material) = game_materials.iter_mut().next().unwrap();
+    game_material.ripple_tex = ripple_images.a.clone();
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+struct GameMaterial {
+    #[uniform(0)]
+    bg_color: Vec4,
+    /// Number of texels used in `pos_radius_tex` and `color_tex`.
+    #[uniform(0)]
+    circle_count: u32,
+    #[uniform(0)]
+    arena_aspect: f32,
+    #[texture(1)]
+    #[sampler(2)]
+    pos_radius_tex: Handle<Image>,
+    #[texture(3)]
+    #[sampler(4)]
+    color_tex: Handle<Image>,
+    #[texture(5)]
+    #[sampler(6)]
+    bg_tex: Handle<Image>,
+    #[texture(7)]
+    #[sampler(8)]
+    ripple_tex: Handle<Image>,
+}
+
+impl Material2d for GameMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "game.wgsl".into()
+    }
+}
+
+/// Must match `MAX_PRESSES` in ripple.wgsl. Presses past this in one frame get no ripple.
+const MAX_RIPPLE_PRESSES: usize = 8;
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+struct RippleMaterial {
+    /// xy: cursor, z: -1 to clear, 1 for hover ripples, w: delta time.
+    #[uniform(0)]
+    mouse_pos_dt: Vec4,
+    /// xyz: `BlobWallHit`, w: arena aspect.
+    #[uniform(0)]
+    blob_pos_hit: Vec4,
+    /// xy: `PointerPresses`, z: 1 if used.
+    #[uniform(0)]
+    presses: [Vec4; MAX_RIPPLE_PRESSES],
+    #[texture(1)]
+    #[sampler(2)]
+    prev_tex: Handle<Image>,
+}
+
+impl Material2d for RippleMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "ripple.wgsl".into()
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct RippleCamera;
+
+fn data_image(data: &[Vec4]) -> Image {
+    Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: data.len() as u32,
+                height: 1,
+                ..default()
+            },
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba32Float,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        },
+        data: Some(cast_slice(data).to_vec()),
+        ..Default::default()
+    }
+}
+
+#[derive(Resource)]
+pub struct RippleImages {
+    pub a: Handle<Image>,
+    pub b: Handle<Image>,
+    pub res: Vec2,
+}
+
+impl RippleImages {
+    pub fn new(res: Vec2, images: &mut Assets<Image>) -> RippleImages {
+        let size = Extent3d {
+            width: res.x as u32,
+            height: res.y as u32,
+            ..default()
+        };
+        let mut image = Image::new_fill(
+            size,
+            TextureDimension::D2,
+            &[0, 0, 0, 0, 0, 0, 0, 0],
+            TextureFormat::Rgba16Float,
+            RenderAssetUsages::default(),
+        );
+        image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+            | TextureUsages::COPY_DST
+            | TextureUsages::RENDER_ATTACHMENT;
+        RippleImages {
+            a: images.add(image.clone()),
+            b: images.add(image),
+            res,
+        }
+    }
+
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.a, &mut self.b);
+    }
+}
+
+fn fullscreen_tri() -> Triangle2d {
+    // lol
+    Triangle2d::new(
+        Vec2::new(-10000., -100000.),
+        Vec2::new(-10000., 10000.),
+        Vec2::new(100000., 10000.),
+    )
+}
@@ -0,0 +1,401 @@
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::mode::GameMode;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::rng::GameRng;
+use crate::rng::NextGameSeed;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::sim::Score;
+use crate::sim::{ArenaAspect, BlobClick, GameState, SimSet, SimTick, spawn_blobs_init_game};
+
+/// Records the clicks of the current game, and optionally plays back a recorded game in place of
+/// user input.
+#[derive(Default)]
+pub struct GlorbyReplayPlugin {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub record: Option<PathBuf>,
+    pub playback: Option<Replay>,
+}
+
+impl Plugin for GlorbyReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayRecorder>()
+            .add_systems(
+                OnEnter(GameState::Start),
+                (
+                    (reset_recorder, restart_playback).before(spawn_blobs_init_game),
+                    restore_replay_arena.after(spawn_blobs_init_game),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    play_replay_clicks.run_if(resource_exists::<ReplayPlayback>),
+                    record_clicks,
+                )
+                    .chain()
+                    .in_set(SimSet::Input),
+            );
+
+        if let Some(replay) = &self.playback {
+            app.insert_resource(Time::<Fixed>::from_duration(replay.timestep))
+                .insert_resource(ArenaAspect(replay.arena_aspect))
+                .insert_resource(replay.mode)
+                .insert_resource(ReplayPlayback {
+                    replay: replay.clone(),
+                    next: 0,
+                })
+                .add_systems(Startup, start_replay);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = &self.record {
+            app.insert_resource(ReplayRecordPath(path.clone()))
+                .add_systems(OnEnter(GameState::Paused), save_replay)
+                .add_systems(OnEnter(GameState::GameOver), save_replay)
+                .add_systems(Last, save_replay.run_if(on_event::<AppExit>));
+        }
+    }
+}
+
+const REPLAY_MAGIC: &[u8; 4] = b"GLRP";
+const REPLAY_VERSION: u8 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReplayClick {
+    pub tick: u64,
+    pub pos: Vec2,
+}
+
+/// Everything needed to reproduce a game: the game mode, the seed, the tick timing, the play field and every
+/// click with the tick it was applied on.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Replay {
+    pub mode: GameMode,
+    pub seed: u32,
+    pub timestep: Duration,
+    pub arena_aspect: f32,
+    pub clicks: Vec<ReplayClick>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownMode(u8),
+    ZeroTimestep,
+    InvalidArenaAspect(f32),
+    Truncated,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::BadMagic => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(v) => write!(f, "unsupported replay version {v}"),
+            ReplayError::UnknownMode(m) => write!(f, "unknown game mode {m}"),
+            ReplayError::ZeroTimestep => write!(f, "replay has a zero timestep"),
+            ReplayError::InvalidArenaAspect(a) => write!(f, "invalid arena aspect {a}"),
+            ReplayError::Truncated => write!(f, "replay file is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    /// Little endian: magic, version, game mode u8, seed u32, timestep nanos u64,
+    /// arena aspect f32, click count u32, then per click: ticks since previous click u32,
+    /// x f32, y f32. Version 1 files have no game mode and are endless games.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(26 + self.clicks.len() * 12);
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.push(REPLAY_VERSION);
+        bytes.push(self.mode.id());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(self.timestep.as_nanos() as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.arena_aspect.to_le_bytes());
+        bytes.extend_from_slice(&(self.clicks.len() as u32).to_le_bytes());
+        let mut prev_tick = 0;
+        for click in &self.clicks {
+            bytes.extend_from_slice(&((click.tick - prev_tick) as u32).to_le_bytes());
+            bytes.extend_from_slice(&click.pos.x.to_le_bytes());
+            bytes.extend_from_slice(&click.pos.y.to_le_bytes());
+            prev_tick = click.tick;
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, ReplayError> {
+        let mut reader = ByteReader(bytes);
+        if reader.take::<4>()? != *REPLAY_MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        let [version] = reader.take::<1>()?;
+        let mode = match version {
+            1 => GameMode::Endless,
+            REPLAY_VERSION => {
+                let [id] = reader.take::<1>()?;
+                GameMode::from_id(id).ok_or(ReplayError::UnknownMode(id))?
+            }
+            _ => return Err(ReplayError::UnsupportedVersion(version)),
+        };
+        let seed = u32::from_le_bytes(reader.take()?);
+        let timestep = Duration::from_nanos(u64::from_le_bytes(reader.take()?));
+        if timestep.is_zero() {
+            return Err(ReplayError::ZeroTimestep);
+        }
+        let arena_aspect = f32::from_le_bytes(reader.take()?);
+        if !arena_aspect.is_finite() || arena_aspect <= 0.0 {
+            return Err(ReplayError::InvalidArenaAspect(arena_aspect));
+        }
+        let count = u32::from_le_bytes(reader.take()?);
+        let mut clicks = Vec::with_capacity(count.min(1 << 16) as usize);
+        let mut tick = 0;
+        for _ in 0..count {
+            tick += u32::from_le_bytes(reader.take()?) as u64;
+            let x = f32::from_le_bytes(reader.take()?);
+            let y = f32::from_le_bytes(reader.take()?);
+            clicks.push(ReplayClick {
+                tick,
+                pos: vec2(x, y),
+            });
+        }
+        Ok(Replay {
+            mode,
+            seed,
+            timestep,
+            arena_aspect,
+            clicks,
+        })
+    }
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl ByteReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        let (head, rest) = self
+            .0
+            .split_first_chunk::<N>()
+            .ok_or(ReplayError::Truncated)?;
+        self.0 = rest;
+        Ok(*head)
+    }
+}
+
+/// Clicks of the game in progress.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ReplayRecorder {
+    pub clicks: Vec<ReplayClick>,
+}
+
+impl ReplayRecorder {
+    pub fn replay(
+        &self,
+        mode: GameMode,
+        seed: u32,
+        timestep: Duration,
+        arena_aspect: f32,
+    ) -> Replay {
+        Replay {
+            mode,
+            seed,
+            timestep,
+            arena_aspect,
+            clicks: self.clicks.clone(),
+        }
+    }
+}
+
+/// Present while a replay is driving the game instead of user input.
+#[derive(Resource, Clone, Debug)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    next: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Resource, Clone, Debug)]
+struct ReplayRecordPath(PathBuf);
+
+fn start_replay(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Start);
+}
+
+fn reset_recorder(mut recorder: ResMut<ReplayRecorder>) {
+    recorder.clicks.clear();
+}
+
+fn restart_playback(
+    playback: Option<ResMut<ReplayPlayback>>,
+    mut next_seed: ResMut<NextGameSeed>,
+    mut mode: ResMut<GameMode>,
+) {
+    if let Some(mut playback) = playback {
+        playback.next = 0;
+        **next_seed = Some(playback.replay.seed);
+        *mode = playback.replay.mode;
+    }
+}
+
+/// Replays keep the play field they were recorded with, whatever the current tuning says.
+fn restore_replay_arena(
+    playback: Option<Res<ReplayPlayback>>,
+    mut arena_aspect: ResMut<ArenaAspect>,
+) {
+    if let Some(playback) = playback {
+        **arena_aspect = playback.replay.arena_aspect;
+    }
+}
+
+fn play_replay_clicks(
+    mut playback: ResMut<ReplayPlayback>,
+    sim_tick: Res<SimTick>,
+    mut clicks: EventWriter<BlobClick>,
+) {
+    while let Some(click) = playback.replay.clicks.get(playback.next) {
+        if click.tick > **sim_tick {
+            break;
+        }
+        clicks.write(BlobClick(click.pos));
+        playback.next += 1;
+    }
+}
+
+fn record_clicks(
+    mut clicks: EventReader<BlobClick>,
+    sim_tick: Res<SimTick>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    for click in clicks.read() {
+        recorder.clicks.push(ReplayClick {
+            tick: **sim_tick,
+            pos: click.0,
+        });
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_replay(
+    recorder: Res<ReplayRecorder>,
+    path: Res<ReplayRecordPath>,
+    rng: Res<GameRng>,
+    fixed_time: Res<Time<Fixed>>,
+    arena_aspect: Res<ArenaAspect>,
+    sim_tick: Res<SimTick>,
+    score: Res<Score>,
+) {
+    // Nothing played yet, don't clobber an existing file.
+    if **sim_tick == 0 {
+        return;
+    }
+    let replay = recorder.replay(
+        score.mode,
+        rng.seed(),
+        fixed_time.timestep(),
+        **arena_aspect,
+    );
+    if let Err(e) = std::fs::write(&path.0, replay.to_bytes()) {
+        error!("Failed to write replay to {}: {e}", path.0.display());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_replay(path: &std::path::Path) -> Result<Replay, Box<dyn std::error::Error>> {
+    Ok(Replay::from_bytes(&std::fs::read(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay() -> Replay {
+        Replay {
+            mode: GameMode::Blitz,
+            seed: 0xdead_beef,
+            timestep: Duration::from_micros(15_625),
+            arena_aspect: 16.0 / 9.0,
+            clicks: vec![
+                ReplayClick {
+                    tick: 3,
+                    pos: vec2(0.25, -0.5),
+                },
+                ReplayClick {
+                    tick: 3,
+                    pos: vec2(-1.0, 0.75),
+                },
+                ReplayClick {
+                    tick: 120,
+                    pos: vec2(1.5, 0.0),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let replay = replay();
+        assert_eq!(Replay::from_bytes(&replay.to_bytes()).unwrap(), replay);
+    }
+
+    #[test]
+    fn reads_version_1_as_endless() {
+        let mut bytes = replay().to_bytes();
+        // Version 1 has no game mode byte.
+        bytes[4] = 1;
+        bytes.remove(5);
+        assert_eq!(
+            Replay::from_bytes(&bytes).unwrap(),
+            Replay {
+                mode: GameMode::Endless,
+                ..replay()
+            }
+        );
+    }
+
+    #[test]
+    fn truncated_input() {
+        let bytes = replay().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Replay::from_bytes(&bytes[..len]).is_err(), "length {len}");
+        }
+        assert!(matches!(
+            Replay::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ReplayError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn rejects_zero_timestep() {
+        let bytes = Replay {
+            timestep: Duration::ZERO,
+            ..replay()
+        }
+        .to_bytes();
+        assert!(matches!(
+            Replay::from_bytes(&bytes),
+            Err(ReplayError::ZeroTimestep)
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_arena_aspect() {
+        for arena_aspect in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let bytes = Replay {
+                arena_aspect,
+                ..replay()
+            }
+            .to_bytes();
+            assert!(matches!(
+                Replay::from_bytes(&bytes),
+                Err(ReplayError::InvalidArenaAspect(_))
+            ));
+        }
+    }
+}
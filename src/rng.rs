@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+use crate::sampling::{uhash, unormf};
+
+/// Counter based RNG for everything the simulation spawns. The same seed always produces the
+/// same sequence, independent of frame timing.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct GameRng {
+    seed: u32,
+    counter: u32,
+}
+
+impl GameRng {
+    pub fn new(seed: u32) -> Self {
+        Self { seed, counter: 0 }
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.counter = self.counter.wrapping_add(1);
+        uhash(self.seed, self.counter)
+    }
+
+    /// In [0, 1]
+    pub fn next_f32(&mut self) -> f32 {
+        unormf(self.next_u32())
+    }
+
+    /// In [-1, 1]
+    pub fn next_f32_signed(&mut self) -> f32 {
+        self.next_f32() * 2.0 - 1.0
+    }
+
+    pub fn next_vec2_signed(&mut self) -> Vec2 {
+        vec2(self.next_f32_signed(), self.next_f32_signed())
+    }
+}
+
+/// Seed for the next game. When `None` a fresh seed is picked as the game starts.
+#[derive(Resource, Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct NextGameSeed(pub Option<u32>);
+
+/// Seed shared by everyone playing the daily challenge on the given (year, month, day).
+pub fn daily_seed((year, month, day): (i32, u32, u32)) -> u32 {
+    uhash(year as u32 * 10000 + month * 100 + day, DAILY_SEED_SALT)
+}
+
+const DAILY_SEED_SALT: u32 = 0x6461_696c;
+
+pub fn fresh_seed(previous_seed: u32, real_time: &Time<Real>) -> u32 {
+    let elapsed = real_time.elapsed();
+    uhash(
+        previous_seed ^ elapsed.subsec_nanos(),
+        elapsed.as_secs() as u32,
+    )
+}
@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+
+use crate::mode::GameMode;
+use crate::sim::Score;
+
+/// Every term of the score formula. Systems award points through this so leaderboards, replays
+/// and game modes all agree on what a run is worth.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct ScoringRules {
+    /// Raw points per clicked blob, scaled by the game speed at the time of the click.
+    pub hit_reward: f32,
+    /// Raw points per blob a chain reaches past the clicked one, scaled by the game speed and
+    /// the chain's combo multiplier.
+    pub chain_hit_reward: f32,
+    /// Raw points per live blob per second.
+    pub survival_per_blob: f32,
+    /// Share of the composite score that ignores accuracy.
+    pub raw_weight: f32,
+    /// Share of the composite score scaled by accuracy.
+    pub accuracy_weight: f32,
+    /// Added to both hits and misses so the first few clicks don't swing the accuracy factor.
+    pub accuracy_prior: f32,
+    /// Extra survival bonus multiplier per blob reached by the biggest active chain.
+    pub combo_step: f32,
+    pub max_combo_multiplier: f32,
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        Self {
+            hit_reward: 5.0,
+            chain_hit_reward: 1.0,
+            survival_per_blob: 0.5,
+            raw_weight: 0.2,
+            accuracy_weight: 0.8,
+            accuracy_prior: 20.0,
+            combo_step: 0.1,
+            max_combo_multiplier: 4.0,
+        }
+    }
+}
+
+impl ScoringRules {
+    pub fn hit_reward(&self, game_speed: f32) -> f32 {
+        self.hit_reward * game_speed
+    }
+
+    pub fn chain_hit_reward(&self, game_speed: f32, reached: u32) -> f32 {
+        self.chain_hit_reward * game_speed * self.combo_multiplier(reached)
+    }
+
+    pub fn survival_bonus(&self, live_blobs: u32, dt: f32) -> f32 {
+        dt * live_blobs as f32 * self.survival_per_blob
+    }
+
+    pub fn combo_multiplier(&self, reached: u32) -> f32 {
+        (1.0 + self.combo_step * reached.saturating_sub(1) as f32).min(self.max_combo_multiplier)
+    }
+
+    pub fn accuracy_factor(&self, hits: u64, misses: u64) -> f32 {
+        (hits as f32 + self.accuracy_prior) / (misses as f32 + self.accuracy_prior)
+    }
+
+    pub fn composite(&self, score: &Score) -> f32 {
+        match score.mode {
+            GameMode::Endless | GameMode::Daily => {
+                score.raw * self.raw_weight
+                    + score.raw
+                        * self.accuracy_factor(score.hits, score.misses)
+                        * self.accuracy_weight
+            }
+            // Survival doesn't matter when the clock ends the game.
+            GameMode::Blitz => score.hit_points * self.accuracy_factor(score.hits, score.misses),
+            GameMode::Zen => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composite_matches_old_formula() {
+        let rules = ScoringRules::default();
+        let score = Score {
+            mode: GameMode::Endless,
+            raw: 1234.5,
+            hits: 40,
+            misses: 7,
+            ..default()
+        };
+        let (raw, hits, misses) = (score.raw, score.hits as f32, score.misses as f32);
+        let old = raw * 0.2 + raw * ((hits + 20.0) / (misses + 20.0)) * 0.8;
+        assert!((rules.composite(&score) - old).abs() < 1e-3);
+    }
+
+    #[test]
+    fn combo_multiplier_is_clamped() {
+        let rules = ScoringRules::default();
+        assert_eq!(rules.combo_multiplier(0), 1.0);
+        assert_eq!(rules.combo_multiplier(1), 1.0);
+        assert!((rules.combo_multiplier(6) - 1.5).abs() < 1e-6);
+        assert_eq!(rules.combo_multiplier(1000), rules.max_combo_multiplier);
+    }
+
+    #[test]
+    fn hit_reward_scales_with_speed() {
+        let rules = ScoringRules::default();
+        assert_eq!(rules.hit_reward(1.0), 5.0);
+        assert_eq!(rules.hit_reward(2.5), 12.5);
+    }
+
+    #[test]
+    fn chain_hit_reward_grows_with_the_chain() {
+        let rules = ScoringRules::default();
+        assert_eq!(rules.chain_hit_reward(2.0, 1), 2.0);
+        assert!((rules.chain_hit_reward(2.0, 11) - 4.0).abs() < 1e-6);
+        assert_eq!(
+            rules.chain_hit_reward(1.0, 1000),
+            rules.chain_hit_reward * rules.max_combo_multiplier
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! CPU copies of the distance functions in game.wgsl, so picking matches what is drawn. Keep the
+//! two in sync.
+
+use bevy::prelude::*;
+
+/// Clickable blobs are highlighted at this fraction of their radius.
+pub const READY_TO_CLICK_SCALE: f32 = 0.8;
+/// Smoothing between clickable highlights.
+pub const READY_TO_CLICK_K: f32 = 0.01;
+
+pub fn sd_circle(p: Vec2, r: f32) -> f32 {
+    p.length() - r
+}
+
+pub fn op_smooth_union(d1: f32, d2: f32, k: f32) -> f32 {
+    let h = (0.5 + 0.5 * (d2 - d1) / k).clamp(0.0, 1.0);
+    d2 + (d1 - d2) * h - k * h * (1.0 - h)
+}
+
+/// Distance to the clickable highlights as drawn by `ready_to_click_map`, `blobs` as
+/// (position, radius).
+pub fn ready_to_click_map(p: Vec2, blobs: impl IntoIterator<Item = (Vec2, f32)>) -> f32 {
+    let mut d = 1.0;
+    for (pos, radius) in blobs {
+        d = op_smooth_union(
+            d,
+            sd_circle(p - pos, radius * READY_TO_CLICK_SCALE),
+            READY_TO_CLICK_K,
+        );
+    }
+    d
+}
+
+/// Clickable blobs under `p`, `blobs` as (entity, position, radius). Every blob whose highlight
+/// contains `p` is picked. When `p` is only inside the smoothed join between highlights the
+/// closest blob is picked.
+pub fn pick(p: Vec2, blobs: &[(Entity, Vec2, f32)]) -> Vec<Entity> {
+    let highlight =
+        |&(_, pos, radius): &(Entity, Vec2, f32)| sd_circle(p - pos, radius * READY_TO_CLICK_SCALE);
+    let picked: Vec<Entity> = blobs
+        .iter()
+        .filter(|blob| highlight(blob) < 0.0)
+        .map(|(entity, ..)| *entity)
+        .collect();
+    if !picked.is_empty() {
+        return picked;
+    }
+    if ready_to_click_map(p, blobs.iter().map(|(_, pos, radius)| (*pos, *radius))) >= 0.0 {
+        return picked;
+    }
+    blobs
+        .iter()
+        .min_by(|a, b| highlight(a).total_cmp(&highlight(b)))
+        .map(|(entity, ..)| *entity)
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+
+    #[test]
+    fn smooth_union_matches_wgsl() {
+        // mix(d2, d1, h) - k * h * (1 - h)
+        assert_close(op_smooth_union(0.3, 0.1, 0.5), 0.055);
+        assert_close(op_smooth_union(0.0, 0.0, 0.2), -0.05);
+        assert_close(op_smooth_union(1.0, 0.2, 0.1), 0.2);
+        assert_close(op_smooth_union(-0.1, 0.4, 0.2), -0.1);
+    }
+
+    #[test]
+    fn pick_inside_highlight() {
+        let blob = Entity::from_raw(1);
+        assert_eq!(
+            pick(vec2(0.05, 0.0), &[(blob, Vec2::ZERO, 0.1)]),
+            vec![blob]
+        );
+    }
+
+    #[test]
+    fn pick_misses_outside_highlight() {
+        // Inside the blob but outside its 0.8 radius highlight.
+        let blob = Entity::from_raw(1);
+        assert!(pick(vec2(0.09, 0.0), &[(blob, Vec2::ZERO, 0.1)]).is_empty());
+    }
+
+    #[test]
+    fn pick_closest_in_smoothed_join() {
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+        let blobs = [(a, vec2(-0.081, 0.0), 0.1), (b, vec2(0.082, 0.0), 0.1)];
+        let p = Vec2::ZERO;
+        assert!(sd_circle(p - blobs[0].1, 0.08) > 0.0);
+        assert!(sd_circle(p - blobs[1].1, 0.08) > 0.0);
+        assert_eq!(pick(p, &blobs), vec![a]);
+    }
+}
@@ -0,0 +1,1120 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::combo::{ChainStats, Combos};
+use crate::mode::GameMode;
+use crate::rng::{GameRng, NextGameSeed, daily_seed, fresh_seed};
+use crate::sampling::hash_noise;
+use crate::scoring::ScoringRules;
+use crate::sdf;
+use crate::spatial::BlobGrid;
+use crate::storage;
+use crate::tuning::GameTuning;
+
+pub const DEFAULT_TICK_RATE: f64 = 60.0;
+
+/// Blob, splash and score simulation. Only needs `MinimalPlugins` + `StatesPlugin`, rendering,
+/// audio and input are layered on top by their own plugins.
+/// Runs in `FixedUpdate` at `tick_rate` Hz so results don't depend on the frame rate.
+pub struct GlorbySimPlugin {
+    pub tick_rate: f64,
+}
+
+impl Default for GlorbySimPlugin {
+    fn default() -> Self {
+        Self {
+            tick_rate: DEFAULT_TICK_RATE,
+        }
+    }
+}
+
+impl Plugin for GlorbySimPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_hz(self.tick_rate))
+            .init_resource::<LiveBlobs>()
+            .init_resource::<GameSpeed>()
+            .init_resource::<Score>()
+            .init_resource::<ScoringRules>()
+            .init_resource::<Combos>()
+            .init_resource::<ArenaAspect>()
+            .init_resource::<GameRng>()
+            .init_resource::<NextGameSeed>()
+            .init_resource::<SimTick>()
+            .init_resource::<GameEnded>()
+            .init_resource::<GameTuning>()
+            .init_resource::<GameMode>()
+            .init_resource::<WaveState>()
+            .init_resource::<BlobGrid>()
+            .add_event::<BlobClick>()
+            .add_event::<BlobClickResult>()
+            .add_event::<BlobWallHit>()
+            .add_event::<BlobMerge>()
+            .init_state::<GameState>()
+            .add_systems(OnEnter(GameState::Start), spawn_blobs_init_game)
+            .add_systems(OnEnter(GameState::GameOver), finish_combos)
+            .configure_sets(
+                FixedUpdate,
+                (SimSet::Prepare, SimSet::Input, SimSet::Simulate)
+                    .chain()
+                    .run_if(in_state(GameState::Running))
+                    .run_if(|ended: Res<GameEnded>| !**ended),
+            )
+            .add_systems(
+                FixedUpdate,
+                (store_previous_positions, advance_sim_tick).in_set(SimSet::Prepare),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_blob_grid,
+                    click_blobs,
+                    shrink_grow_blobs,
+                    leech_blobs,
+                    respawn_blobs,
+                    set_blob_state,
+                    count_live_blobs,
+                    spawn_waves,
+                    move_blobs,
+                    merge_blobs,
+                    collide_blobs,
+                    update_blob_grid,
+                    splash_blobs,
+                    update_combos,
+                    update_score,
+                )
+                    .chain()
+                    .in_set(SimSet::Simulate),
+            )
+            .add_systems(
+                FixedUpdate,
+                check_game_over
+                    .after(SimSet::Simulate)
+                    .run_if(in_state(GameState::Running))
+                    .run_if(|ended: Res<GameEnded>| !**ended),
+            );
+    }
+}
+
+/// Ordering of a simulation tick. `Input` is where `BlobClick` events for the tick are sent.
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SimSet {
+    Prepare,
+    Input,
+    Simulate,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States)]
+pub enum GameState {
+    #[default]
+    Paused,
+    Running,
+    Start,
+    GameOver,
+}
+
+/// Width / height of the play field. The field spans `[-aspect, aspect] x [-1, 1]`, y down, and
+/// doesn't depend on the window.
+#[derive(Resource, Clone, Copy, Deref, DerefMut)]
+pub struct ArenaAspect(pub f32);
+
+impl ArenaAspect {
+    /// Window pixels per play field unit, with the field letterboxed or pillarboxed to fit.
+    /// Must match the mapping in game.wgsl and ripple.wgsl.
+    pub fn scale(self, window_size: Vec2) -> f32 {
+        window_size.y.min(window_size.x / self.0) * 0.5
+    }
+
+    /// Maps a window position in pixels to the play field. Positions in the bars around the
+    /// field map outside it.
+    pub fn window_to_arena(self, window_pos: Vec2, window_size: Vec2) -> Vec2 {
+        (window_pos - window_size * 0.5) / self.scale(window_size)
+    }
+
+    /// Whether a play field position is inside the arena rather than in the bars around it.
+    pub fn contains(self, arena_pos: Vec2) -> bool {
+        arena_pos.abs().cmple(vec2(self.0, 1.0)).all()
+    }
+
+    /// Inverse of `window_to_arena`.
+    pub fn arena_to_window(self, arena_pos: Vec2, window_size: Vec2) -> Vec2 {
+        arena_pos * self.scale(window_size) + window_size * 0.5
+    }
+}
+
+impl Default for ArenaAspect {
+    fn default() -> Self {
+        Self(16.0 / 9.0)
+    }
+}
+
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+pub struct BlobSizeRadius(pub f32);
+
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+pub struct BlobPosition(pub Vec2);
+
+/// Position at the start of the current tick, for interpolating between ticks when rendering.
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+pub struct BlobPreviousPosition(pub Vec2);
+
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+pub struct BlobVelocity(pub Vec2);
+
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+pub struct BlobColor(pub Vec3);
+
+#[derive(Clone, Copy, Component)]
+pub struct BlobCanBeClicked;
+
+/// What a blob does when clicked or splashed, see `BlobKindTuning`. Splashes have no kind.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlobKind {
+    #[default]
+    Normal,
+    /// Clicking it also splashes every blob reaching within `bomb_radius`. A splash makes it
+    /// splash twice as much.
+    Bomb,
+    /// The first click or splash only cracks the armor and turns it `Normal`.
+    Armored,
+    /// Splits into two blobs that grow half as much when clicked.
+    Splitter,
+    /// Drains blobs within `leech_radius`. Splashes feed it instead of splashing on.
+    Leech,
+}
+
+/// Remaining fraction of the grow animation, 0.0 when not growing.
+#[derive(Clone, Copy, Component, Deref, DerefMut)]
+pub struct BlobGrowing(f32);
+
+#[derive(Clone, Copy, Resource, Deref, DerefMut, Default)]
+pub struct LiveBlobs(pub u32);
+
+/// Simulation ticks since the game started.
+#[derive(Clone, Copy, Resource, Deref, DerefMut, Default, Debug)]
+pub struct SimTick(pub u64);
+
+/// Set by the tick that ends the game. `GameState::GameOver` only takes effect at the next state
+/// transition, this stops any fixed ticks still queued in the same frame.
+#[derive(Clone, Copy, Resource, Deref, DerefMut, Default, Debug)]
+pub struct GameEnded(pub bool);
+
+#[derive(Clone, Component)]
+pub struct SplashBlob {
+    age: f32,
+    spawned_by: Vec<Entity>,
+    chain: u32,
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Score {
+    pub mode: GameMode,
+    pub raw: f32,
+    /// The part of `raw` awarded for clicked blobs and the chains they set off.
+    pub hit_points: f32,
+    pub hits: u64,
+    pub misses: u64,
+    /// Seconds played with blobs still alive.
+    pub time: f32,
+    pub best_chain: ChainStats,
+    pub peak_speed: f32,
+    /// UTC (year, month, day) the game started on, a daily run counts for this day.
+    pub date: (i32, u32, u32),
+}
+
+impl Score {
+    /// Fraction of clicks that hit a blob, 1.0 before the first click.
+    pub fn accuracy(&self) -> f32 {
+        let clicks = self.hits + self.misses;
+        if clicks == 0 {
+            1.0
+        } else {
+            self.hits as f32 / clicks as f32
+        }
+    }
+
+    pub fn composite(&self, rules: &ScoringRules) -> f32 {
+        rules.composite(self)
+    }
+
+    pub fn add_hit_points(&mut self, points: f32) {
+        self.raw += points;
+        self.hit_points += points;
+    }
+
+    /// Keeps `chain` if it beats the best so far.
+    pub fn record_chain(&mut self, chain: ChainStats) {
+        if chain.is_better_than(&self.best_chain) {
+            self.best_chain = chain;
+        }
+    }
+
+    /// Seconds until a timed game ends.
+    pub fn time_left(&self) -> Option<f32> {
+        self.mode
+            .time_limit()
+            .map(|limit| (limit - self.time).max(0.0))
+    }
+}
+
+/// Progress through `WaveTuning`. The blobs a game starts with are wave 1.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct WaveState {
+    /// Latest wave to arrive.
+    pub number: u32,
+    /// Waves that still had blobs alive when the next one was due.
+    pub cleared: u32,
+    pub since_last: f32,
+}
+
+#[derive(Resource, Clone, Copy, Deref, DerefMut)]
+pub struct GameSpeed(pub f32);
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self(GameTuning::default().start_speed)
+    }
+}
+
+/// A click at a play field position (same space as `BlobPosition`).
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BlobClick(pub Vec2);
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BlobClickResult {
+    pub hit: bool,
+}
+
+/// xy: position of the wall contact, z: radius of the blob that hit it.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BlobWallHit(pub Vec3);
+
+/// Two blobs combined into `merged`.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BlobMerge {
+    pub merged: Entity,
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+pub(crate) fn spawn_blobs_init_game(
+    mut commands: Commands,
+    existing_blobs: Query<Entity, With<BlobSizeRadius>>,
+    mut score: ResMut<Score>,
+    mut combos: ResMut<Combos>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut game_speed: ResMut<GameSpeed>,
+    mut live_blobs: ResMut<LiveBlobs>,
+    mut sim_tick: ResMut<SimTick>,
+    mut game_ended: ResMut<GameEnded>,
+    mut rng: ResMut<GameRng>,
+    mut next_seed: ResMut<NextGameSeed>,
+    real_time: Res<Time<Real>>,
+    tuning: Res<GameTuning>,
+    mode: Res<GameMode>,
+    mut waves: ResMut<WaveState>,
+    mut arena_aspect: ResMut<ArenaAspect>,
+) {
+    for entity in existing_blobs {
+        commands.entity(entity).despawn();
+    }
+
+    let today = storage::today();
+    *score = Score {
+        mode: *mode,
+        date: today,
+        ..default()
+    };
+    *combos = Combos::default();
+    *game_speed = GameSpeed(tuning.start_speed);
+    *sim_tick = SimTick::default();
+    **game_ended = false;
+    *arena_aspect = ArenaAspect(tuning.arena_aspect);
+    *waves = WaveState {
+        number: 1,
+        ..default()
+    };
+
+    let seed = next_seed.take().unwrap_or_else(|| {
+        if mode.is_daily() {
+            daily_seed(today)
+        } else {
+            fresh_seed(rng.seed(), &real_time)
+        }
+    });
+    *rng = GameRng::new(seed);
+
+    for _ in 0..tuning.init_count {
+        let size = tuning.init_size_min + rng.next_f32() * tuning.init_size_range;
+        let pos = rng.next_vec2_signed() * 0.5;
+        let vel_rng = rng.next_vec2_signed();
+        let color = vec3(rng.next_f32(), rng.next_f32(), rng.next_f32());
+        let kind = tuning.kinds.pick(rng.next_f32());
+
+        commands.spawn((
+            BlobSizeRadius(size),
+            BlobPosition(pos),
+            BlobPreviousPosition(pos),
+            BlobVelocity(0.2 * vel_rng.signum() + vel_rng * 0.1),
+            BlobColor(0.2 + color * 0.5),
+            BlobGrowing(0.0),
+            kind,
+        ));
+    }
+
+    *live_blobs = LiveBlobs(tuning.init_count);
+
+    next_state.set(GameState::Running);
+}
+
+/// Runs before clicks and again before splashes since blobs move in between.
+fn update_blob_grid(
+    blobs: Query<(Entity, &BlobSizeRadius, &BlobPosition), Without<SplashBlob>>,
+    mut grid: ResMut<BlobGrid>,
+) {
+    grid.rebuild(
+        blobs
+            .iter()
+            .filter(|(_, size, _)| ***size > 0.0)
+            .map(|(entity, size, pos)| (entity, **pos, **size)),
+    );
+}
+
+fn store_previous_positions(mut blobs: Query<(&BlobPosition, &mut BlobPreviousPosition)>) {
+    for (pos, mut prev_pos) in &mut blobs {
+        **prev_pos = **pos;
+    }
+}
+
+fn advance_sim_tick(mut sim_tick: ResMut<SimTick>) {
+    **sim_tick += 1;
+}
+
+fn count_live_blobs(
+    blobs: Query<&BlobSizeRadius, Without<SplashBlob>>,
+    mut live_blobs: ResMut<LiveBlobs>,
+) {
+    let mut alive_count = 0;
+    for blob_size in blobs {
+        if **blob_size > 0.0 {
+            alive_count += 1;
+        }
+    }
+    *live_blobs = LiveBlobs(alive_count);
+}
+
+fn shrink_grow_blobs(
+    mut blobs: Query<(&mut BlobSizeRadius, &mut BlobGrowing), Without<SplashBlob>>,
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+) {
+    for (i, (mut blob_size, mut blob_growing)) in blobs.iter_mut().enumerate() {
+        let ui = i as u32;
+        if **blob_growing > 0.0 {
+            **blob_size += time.delta_secs()
+                * tuning.grow_speed
+                * (hash_noise(ui, ui, ui) * 0.5 + 0.5).clamp(1.0, 1.0);
+            **blob_growing = (**blob_growing - time.delta_secs() / tuning.grow_seconds).max(0.0);
+        } else {
+            **blob_size -= time.delta_secs() * tuning.shrink_speed;
+        }
+
+        //**blob_size = blob_size.max(0.0);
+    }
+}
+
+/// Brings blobs that shrank away back at a random edge, heading into the play field.
+fn respawn_blobs(
+    mut blobs: Query<
+        (
+            &mut BlobSizeRadius,
+            &mut BlobPosition,
+            &mut BlobPreviousPosition,
+            &mut BlobVelocity,
+            &mut BlobGrowing,
+            &mut BlobKind,
+        ),
+        Without<SplashBlob>,
+    >,
+    score: Res<Score>,
+    arena_aspect: Res<ArenaAspect>,
+    mut rng: ResMut<GameRng>,
+    tuning: Res<GameTuning>,
+) {
+    if !score.mode.respawns_blobs() {
+        return;
+    }
+    for (mut size, mut pos, mut prev_pos, mut vel, mut growing, mut kind) in &mut blobs {
+        if **size > 0.0 {
+            continue;
+        }
+        **size = tuning.init_size_min + rng.next_f32() * tuning.init_size_range;
+        (**pos, **vel) = edge_spawn(&mut rng, **arena_aspect, **size);
+        **prev_pos = **pos;
+        **growing = 0.0;
+        *kind = tuning.kinds.pick(rng.next_f32());
+    }
+}
+
+/// Sends the next wave when it's due, or early when few blobs are left. A wave that arrives on
+/// schedule with blobs still alive clears the previous one.
+fn spawn_waves(
+    mut commands: Commands,
+    mut waves: ResMut<WaveState>,
+    mut score: ResMut<Score>,
+    mut live_blobs: ResMut<LiveBlobs>,
+    mut rng: ResMut<GameRng>,
+    arena_aspect: Res<ArenaAspect>,
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+) {
+    if !score.mode.has_waves() {
+        return;
+    }
+    let Some(wave) = waves
+        .number
+        .checked_sub(1)
+        .and_then(|i| tuning.waves.waves.get(i as usize))
+    else {
+        return;
+    };
+    waves.since_last += time.delta_secs();
+    let due = waves.since_last >= tuning.waves.interval;
+    if !due && live_blobs.0 >= tuning.waves.live_threshold {
+        return;
+    }
+    if due && live_blobs.0 > 0 {
+        score.raw += tuning.waves.clear_bonus;
+        waves.cleared += 1;
+    }
+    waves.number += 1;
+    waves.since_last = 0.0;
+
+    for _ in 0..wave.count {
+        let size = wave.size_min + rng.next_f32() * wave.size_range;
+        let (pos, vel) = edge_spawn(&mut rng, **arena_aspect, size);
+        let color = if wave.palette.is_empty() {
+            0.2 + vec3(rng.next_f32(), rng.next_f32(), rng.next_f32()) * 0.5
+        } else {
+            let (r, g, b) = wave.palette[rng.next_u32() as usize % wave.palette.len()];
+            vec3(r, g, b)
+        };
+        let kind = tuning.kinds.pick(rng.next_f32());
+        commands.spawn((
+            BlobSizeRadius(size),
+            BlobPosition(pos),
+            BlobPreviousPosition(pos),
+            BlobVelocity(vel * wave.speed),
+            BlobColor(color),
+            BlobGrowing(0.0),
+            kind,
+        ));
+    }
+    // Counted again next tick, but the game mustn't end before then.
+    live_blobs.0 += wave.count;
+}
+
+/// Position just inside a random edge and a starting velocity heading into the play field.
+fn edge_spawn(rng: &mut GameRng, arena_aspect: f32, size: f32) -> (Vec2, Vec2) {
+    let vel_rng = rng.next_vec2_signed();
+    let mut vel = 0.2 * vel_rng.signum() + vel_rng * 0.1;
+    let along = rng.next_f32_signed();
+    let half_size = vec2(arena_aspect, 1.0) - size;
+    let pos = match rng.next_u32() % 4 {
+        0 => {
+            vel.x = vel.x.abs();
+            vec2(-half_size.x, along * half_size.y)
+        }
+        1 => {
+            vel.x = -vel.x.abs();
+            vec2(half_size.x, along * half_size.y)
+        }
+        2 => {
+            vel.y = vel.y.abs();
+            vec2(along * half_size.x, -half_size.y)
+        }
+        _ => {
+            vel.y = -vel.y.abs();
+            vec2(along * half_size.x, half_size.y)
+        }
+    };
+    (pos, vel)
+}
+
+/// Leeches take size from the blobs around them.
+fn leech_blobs(
+    mut blobs: Query<(Entity, &mut BlobSizeRadius, &BlobPosition, &BlobKind), Without<SplashBlob>>,
+    grid: Res<BlobGrid>,
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+) {
+    let leeches: Vec<(Entity, Vec2)> = blobs
+        .iter()
+        .filter(|(_, size, _, kind)| **kind == BlobKind::Leech && ***size > 0.0)
+        .map(|(entity, _, pos, _)| (entity, **pos))
+        .collect();
+    let drain = tuning.kinds.leech_drain * time.delta_secs();
+    for (leech, leech_pos) in leeches {
+        let mut drained = 0.0;
+        for entity in grid.in_circle(leech_pos, tuning.kinds.leech_radius) {
+            let Ok((entity, mut size, pos, _)) = blobs.get_mut(entity) else {
+                continue;
+            };
+            if entity != leech
+                && **size > 0.0
+                && pos.distance(leech_pos) < tuning.kinds.leech_radius
+            {
+                let taken = drain.min(**size);
+                **size -= taken;
+                drained += taken;
+            }
+        }
+        if let Ok((_, mut size, _, _)) = blobs.get_mut(leech) {
+            **size += drained * 0.5;
+        }
+    }
+}
+
+fn set_blob_state(
+    mut commands: Commands,
+    blobs: Query<(Entity, &BlobSizeRadius, &BlobGrowing), Without<SplashBlob>>,
+    tuning: Res<GameTuning>,
+) {
+    for (entity, blob_size, growing) in blobs {
+        if **blob_size < tuning.clickable_size && **growing == 0.0 {
+            commands.entity(entity).insert(BlobCanBeClicked);
+        } else {
+            commands.entity(entity).remove::<BlobCanBeClicked>();
+        }
+    }
+}
+
+fn move_blobs(
+    blobs: Query<(
+        &BlobSizeRadius,
+        &mut BlobPosition,
+        &mut BlobVelocity,
+        &BlobColor,
+        Has<SplashBlob>,
+    )>,
+    arena_aspect: Res<ArenaAspect>,
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+    score: Res<Score>,
+    mut game_speed: ResMut<GameSpeed>,
+    mut wall_hits: EventWriter<BlobWallHit>,
+) {
+    if score.mode.speed_ramps() {
+        **game_speed += (time.delta_secs() * tuning.speed_ramp) / **game_speed;
+    }
+    let half_extent = vec2(**arena_aspect, 1.0);
+    let mut hit_pos_rad = None;
+    for (size, mut pos, mut vel, _color, splash_blob) in blobs {
+        **pos += **vel * time.delta_secs() * **game_speed;
+
+        if !splash_blob
+            && **size > 0.0
+            && let Some(contact) = bounce_off_walls(&mut pos, &mut vel, **size, half_extent)
+        {
+            hit_pos_rad = Some(contact.extend(**size));
+        }
+    }
+    if let Some(hit_pos) = hit_pos_rad {
+        wall_hits.write(BlobWallHit(hit_pos));
+    }
+}
+
+/// Keeps a blob inside `[-half_extent, half_extent]`, pushing it back in when it overlaps a wall
+/// after growing or the arena shrinking. Velocity is only reflected when moving into a wall, so a
+/// blob stuck against one doesn't flip every tick. Returns the wall contact point if it bounced.
+/// A blob bigger than the arena is centered on that axis.
+pub(crate) fn bounce_off_walls(
+    pos: &mut Vec2,
+    vel: &mut Vec2,
+    radius: f32,
+    half_extent: Vec2,
+) -> Option<Vec2> {
+    let mut contact = None;
+    for axis in 0..2 {
+        let limit = half_extent[axis] - radius;
+        if limit <= 0.0 {
+            pos[axis] = 0.0;
+            continue;
+        }
+        if pos[axis] < -limit {
+            pos[axis] = -limit;
+            if vel[axis] < 0.0 {
+                vel[axis] = -vel[axis];
+                let mut point = *pos;
+                point[axis] = -half_extent[axis];
+                contact = Some(point);
+            }
+        } else if pos[axis] > limit {
+            pos[axis] = limit;
+            if vel[axis] > 0.0 {
+                vel[axis] = -vel[axis];
+                let mut point = *pos;
+                point[axis] = half_extent[axis];
+                contact = Some(point);
+            }
+        }
+    }
+    contact
+}
+
+/// Combines blobs that overlap enough into the first of the two, the other is despawned.
+fn merge_blobs(
+    mut commands: Commands,
+    mut blobs: Query<
+        (
+            Entity,
+            &mut BlobSizeRadius,
+            &mut BlobPosition,
+            &mut BlobPreviousPosition,
+            &mut BlobVelocity,
+            &mut BlobColor,
+            &mut BlobGrowing,
+            &mut BlobKind,
+        ),
+        Without<SplashBlob>,
+    >,
+    tuning: Res<GameTuning>,
+    mut merges: EventWriter<BlobMerge>,
+) {
+    if !tuning.merge.enabled {
+        return;
+    }
+    let mut pairs = blobs.iter_combinations_mut();
+    while let Some([a, b]) = pairs.fetch_next() {
+        let (
+            entity,
+            mut size_a,
+            mut pos_a,
+            mut prev_pos_a,
+            mut vel_a,
+            mut color_a,
+            mut growing_a,
+            mut kind_a,
+        ) = a;
+        let (other, mut size_b, pos_b, _, vel_b, color_b, growing_b, kind_b) = b;
+        if **size_a <= 0.0 || **size_b <= 0.0 {
+            continue;
+        }
+        let overlap = **size_a + **size_b - pos_a.distance(**pos_b);
+        if overlap < tuning.merge.overlap * 2.0 * size_a.min(**size_b) {
+            continue;
+        }
+        // Mass goes with area, so radius squared.
+        let mass_a = **size_a * **size_a;
+        let mass_b = **size_b * **size_b;
+        let weight_b = mass_b / (mass_a + mass_b);
+        **size_a = (mass_a + mass_b).sqrt();
+        **pos_a = pos_a.lerp(**pos_b, weight_b);
+        // Don't interpolate a slide across the merge tick.
+        **prev_pos_a = **pos_a;
+        **vel_a = vel_a.lerp(**vel_b, weight_b);
+        **color_a = color_a.lerp(**color_b, weight_b);
+        **growing_a = growing_a.max(**growing_b);
+        if *kind_a == BlobKind::Normal {
+            *kind_a = *kind_b;
+        }
+        **size_b = 0.0;
+        commands.entity(other).despawn();
+        merges.write(BlobMerge {
+            merged: entity,
+            position: **pos_a,
+            radius: **size_a,
+        });
+    }
+}
+
+/// Pushes overlapping blobs apart and exchanges momentum between them.
+fn collide_blobs(
+    mut blobs: Query<(&BlobSizeRadius, &mut BlobPosition, &mut BlobVelocity), Without<SplashBlob>>,
+    tuning: Res<GameTuning>,
+) {
+    if !tuning.physics.enabled {
+        return;
+    }
+    let mut pairs = blobs.iter_combinations_mut();
+    while let Some(
+        [
+            (size_a, mut pos_a, mut vel_a),
+            (size_b, mut pos_b, mut vel_b),
+        ],
+    ) = pairs.fetch_next()
+    {
+        if **size_a <= 0.0 || **size_b <= 0.0 {
+            continue;
+        }
+        let delta = **pos_b - **pos_a;
+        let distance = delta.length();
+        let overlap = **size_a + **size_b - distance;
+        if overlap <= 0.0 {
+            continue;
+        }
+        let normal = delta.try_normalize().unwrap_or(Vec2::X);
+        let inv_mass_a = 1.0 / (**size_a * **size_a);
+        let inv_mass_b = 1.0 / (**size_b * **size_b);
+        let inv_mass_sum = inv_mass_a + inv_mass_b;
+
+        **pos_a -= normal * overlap * inv_mass_a / inv_mass_sum;
+        **pos_b += normal * overlap * inv_mass_b / inv_mass_sum;
+
+        let approach = (**vel_b - **vel_a).dot(normal);
+        if approach < 0.0 {
+            let impulse = -(1.0 + tuning.physics.restitution) * approach / inv_mass_sum;
+            **vel_a -= normal * impulse * inv_mass_a;
+            **vel_b += normal * impulse * inv_mass_b;
+        }
+    }
+}
+
+fn splash_blobs(
+    mut commands: Commands,
+    mut blobs: Query<
+        (
+            Entity,
+            &mut BlobSizeRadius,
+            &mut BlobPosition,
+            &mut BlobVelocity,
+            &BlobColor,
+            &mut BlobGrowing,
+            &mut BlobKind,
+        ),
+        Without<SplashBlob>,
+    >,
+    mut splash_blobs: Query<(
+        Entity,
+        &mut BlobSizeRadius,
+        &mut BlobPosition,
+        &mut BlobVelocity,
+        &BlobColor,
+        &mut SplashBlob,
+    )>,
+    time: Res<Time>,
+    mut game_speed: ResMut<GameSpeed>,
+    mut rng: ResMut<GameRng>,
+    mut combos: ResMut<Combos>,
+    live_blobs: Res<LiveBlobs>,
+    mut grid: ResMut<BlobGrid>,
+    tuning: Res<GameTuning>,
+    rules: Res<ScoringRules>,
+    mut score: ResMut<Score>,
+) {
+    if score.mode.speed_ramps() {
+        **game_speed += (time.delta_secs() * tuning.speed_ramp) / **game_speed;
+    }
+
+    let splashes_to_spawn = tuning.splash_splashes.count(live_blobs.0);
+
+    for (splash_entity, mut splash_size, splash_pos, _splash_vel, _splash_color, mut splash_blob) in
+        splash_blobs.iter_mut()
+    {
+        splash_blob.age -= time.delta_secs() * 0.1;
+        **splash_size = splash_blob.age * tuning.splash_start_size;
+        if splash_blob.age <= 0.0 {
+            commands.entity(splash_entity).despawn();
+            continue;
+        }
+        // Later splashes this tick have to find the blob at its new size.
+        let mut grown = None;
+        for entity in grid.at_point(**splash_pos) {
+            let Ok((entity, mut size, pos, _vel, color, _growing, mut kind)) =
+                blobs.get_mut(entity)
+            else {
+                continue;
+            };
+            if splash_blob.spawned_by.contains(&entity) {
+                continue;
+            }
+            if splash_pos.distance(**pos) < **size {
+                commands.entity(splash_entity).despawn();
+                let splashes_to_spawn = match *kind {
+                    BlobKind::Armored => {
+                        *kind = BlobKind::Normal;
+                        break;
+                    }
+                    BlobKind::Leech => {
+                        **size += **splash_size;
+                        grown = Some((entity, **pos, **size));
+                        break;
+                    }
+                    BlobKind::Bomb => {
+                        *kind = BlobKind::Normal;
+                        splashes_to_spawn * 2
+                    }
+                    BlobKind::Normal | BlobKind::Splitter => splashes_to_spawn,
+                };
+                //**growing = growing.max(splash_blob.age * 0.00001);
+                **size += **splash_size * 0.4 + tuning.splash_start_size * 0.1; // TODO use area, smooth anim
+                grown = Some((entity, **pos, **size));
+                if let Some(chain) =
+                    combos.hit(splash_blob.chain, splash_blob.spawned_by.len() as u32 + 1)
+                {
+                    score.add_hit_points(rules.chain_hit_reward(**game_speed, chain.reached));
+                }
+                if splash_blob.spawned_by.len() < 4 {
+                    let mut new_spawned_by = splash_blob.spawned_by.clone();
+                    new_spawned_by.push(entity);
+                    spawn_splash(
+                        &mut commands,
+                        &mut rng,
+                        &tuning,
+                        new_spawned_by,
+                        splash_blob.chain,
+                        &pos,
+                        color,
+                        splashes_to_spawn,
+                    );
+                }
+                break;
+            }
+        }
+        if let Some((entity, pos, radius)) = grown {
+            grid.grow(entity, pos, radius);
+        }
+    }
+}
+
+fn click_blobs(
+    mut commands: Commands,
+    mut clicks: EventReader<BlobClick>,
+    mut blobs: Query<
+        (
+            Entity,
+            &mut BlobSizeRadius,
+            &mut BlobPosition,
+            &BlobVelocity,
+            &BlobColor,
+            Has<BlobCanBeClicked>,
+            &mut BlobGrowing,
+            &mut BlobKind,
+        ),
+        Without<SplashBlob>,
+    >,
+    mut score: ResMut<Score>,
+    rules: Res<ScoringRules>,
+    game_speed: Res<GameSpeed>,
+    mut rng: ResMut<GameRng>,
+    mut combos: ResMut<Combos>,
+    live_blobs: Res<LiveBlobs>,
+    grid: Res<BlobGrid>,
+    tuning: Res<GameTuning>,
+    mut click_results: EventWriter<BlobClickResult>,
+) {
+    let splashes_to_spawn = tuning.click_splashes.count(live_blobs.0);
+
+    for click in clicks.read() {
+        let mut hit = false;
+        let mut chain = None;
+        let mut bombs = vec![];
+        // Hit test against the highlight players see, not the simulated circle.
+        let clickable: Vec<(Entity, Vec2, f32)> = grid
+            .at_point(click.0)
+            .filter_map(|entity| blobs.get(entity).ok())
+            .filter(|(.., can_be_clicked, _, _)| *can_be_clicked)
+            .map(|(entity, size, pos, ..)| (entity, **pos, **size))
+            .collect();
+        for entity in sdf::pick(click.0, &clickable) {
+            let Ok((entity, mut size, mut pos, vel, color, _, mut blob_growing, mut kind)) =
+                blobs.get_mut(entity)
+            else {
+                continue;
+            };
+            score.hits += 1;
+            hit = true;
+            if *kind == BlobKind::Armored {
+                *kind = BlobKind::Normal;
+                continue;
+            }
+            //**size += 0.3;
+            // Clicking while a chain is going pays like the survival bonus does.
+            let multiplier = combos
+                .current()
+                .map_or(1.0, |chain| rules.combo_multiplier(chain.reached));
+            score.add_hit_points(rules.hit_reward(**game_speed) * multiplier);
+            **blob_growing = 1.0;
+            let chain = *chain.get_or_insert_with(|| combos.start());
+            let center = *pos;
+            match *kind {
+                BlobKind::Bomb => bombs.push((entity, **pos, chain)),
+                BlobKind::Splitter => {
+                    // Two halves with the same total area, pushed apart along the velocity so
+                    // they don't start out fully overlapped.
+                    **blob_growing = 0.5;
+                    **size /= std::f32::consts::SQRT_2;
+                    let offset = vel.try_normalize().unwrap_or(Vec2::X) * **size;
+                    **pos += offset;
+                    commands.spawn((
+                        *size,
+                        BlobPosition(*center - offset),
+                        BlobPreviousPosition(*center - offset),
+                        BlobVelocity(-**vel),
+                        *color,
+                        BlobGrowing(0.5),
+                        BlobKind::Normal,
+                    ));
+                }
+                BlobKind::Normal | BlobKind::Armored | BlobKind::Leech => {}
+            }
+            *kind = BlobKind::Normal;
+            spawn_splash(
+                &mut commands,
+                &mut rng,
+                &tuning,
+                vec![entity],
+                chain,
+                &center,
+                color,
+                splashes_to_spawn,
+            );
+        }
+        for (bomb, bomb_pos, chain) in bombs {
+            for entity in grid.in_circle(bomb_pos, tuning.kinds.bomb_radius) {
+                let Ok((entity, mut size, pos, ..)) = blobs.get_mut(entity) else {
+                    continue;
+                };
+                if entity != bomb
+                    && **size > 0.0
+                    && pos.distance(bomb_pos) < tuning.kinds.bomb_radius + **size
+                {
+                    **size += tuning.splash_start_size * 0.5;
+                    if let Some(chain) = combos.hit(chain, 2) {
+                        score.add_hit_points(rules.chain_hit_reward(**game_speed, chain.reached));
+                    }
+                }
+            }
+        }
+        if !hit {
+            score.misses += 1;
+        }
+        click_results.write(BlobClickResult { hit });
+    }
+}
+
+fn spawn_splash(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    tuning: &GameTuning,
+    spawned_by: Vec<Entity>,
+    chain: u32,
+    pos: &BlobPosition,
+    color: &BlobColor,
+    count: u32,
+) {
+    for _ in 0..count {
+        let vel_rng = rng.next_vec2_signed();
+        commands.spawn((
+            BlobSizeRadius(tuning.splash_start_size),
+            *pos,
+            BlobPreviousPosition(**pos),
+            BlobVelocity(0.3 * vel_rng.signum() + vel_rng * 0.3),
+            BlobColor(**color * 0.9),
+            SplashBlob {
+                age: 1.0,
+                spawned_by: spawned_by.clone(),
+                chain,
+            },
+        ));
+    }
+}
+
+/// Ends chains with no splashes left and keeps the best one in `Score`.
+fn update_combos(
+    splash_blobs: Query<&SplashBlob>,
+    mut combos: ResMut<Combos>,
+    mut score: ResMut<Score>,
+    time: Res<Time>,
+) {
+    combos.active.retain_mut(|(id, stats)| {
+        stats.duration += time.delta_secs();
+        if splash_blobs.iter().any(|splash| splash.chain == *id) {
+            return true;
+        }
+        score.record_chain(*stats);
+        false
+    });
+}
+
+/// Chains still going when the game ends count too.
+fn finish_combos(mut combos: ResMut<Combos>, mut score: ResMut<Score>) {
+    for (_, stats) in combos.active.drain(..) {
+        score.record_chain(stats);
+    }
+}
+
+fn update_score(
+    mut score: ResMut<Score>,
+    rules: Res<ScoringRules>,
+    combos: Res<Combos>,
+    time: Res<Time>,
+    live_blobs: Res<LiveBlobs>,
+    game_speed: Res<GameSpeed>,
+) {
+    let multiplier = combos
+        .current()
+        .map_or(1.0, |chain| rules.combo_multiplier(chain.reached));
+    score.raw += rules.survival_bonus(live_blobs.0, time.delta_secs()) * multiplier;
+    if live_blobs.0 > 0 {
+        score.time += time.delta_secs();
+    }
+    score.peak_speed = score.peak_speed.max(**game_speed);
+}
+
+fn check_game_over(
+    live_blobs: Res<LiveBlobs>,
+    score: Res<Score>,
+    mut game_ended: ResMut<GameEnded>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let out_of_time = score.time_left() == Some(0.0);
+    let all_gone = live_blobs.0 == 0 && !score.mode.respawns_blobs();
+    if all_gone || out_of_time {
+        **game_ended = true;
+        next_state.set(GameState::GameOver);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF_EXTENT: Vec2 = Vec2::new(1.5, 1.0);
+
+    #[test]
+    fn overlapping_blob_moving_away_is_clamped() {
+        let mut pos = vec2(1.45, 0.0);
+        let mut vel = vec2(-0.2, 0.1);
+        assert_eq!(bounce_off_walls(&mut pos, &mut vel, 0.1, HALF_EXTENT), None);
+        assert!(pos.abs_diff_eq(vec2(1.4, 0.0), 1e-6));
+        assert_eq!(vel, vec2(-0.2, 0.1));
+    }
+
+    #[test]
+    fn blob_moving_into_wall_bounces() {
+        let mut pos = vec2(0.0, -0.95);
+        let mut vel = vec2(0.1, -0.2);
+        let contact = bounce_off_walls(&mut pos, &mut vel, 0.1, HALF_EXTENT);
+        assert!(pos.abs_diff_eq(vec2(0.0, -0.9), 1e-6));
+        assert_eq!(vel, vec2(0.1, 0.2));
+        assert_eq!(contact, Some(vec2(0.0, -1.0)));
+    }
+
+    #[test]
+    fn grown_blob_is_pushed_back_inside() {
+        let mut pos = vec2(-1.2, 0.7);
+        let mut vel = Vec2::ZERO;
+        bounce_off_walls(&mut pos, &mut vel, 0.5, HALF_EXTENT);
+        assert!(pos.abs_diff_eq(vec2(-1.0, 0.5), 1e-6));
+    }
+
+    #[test]
+    fn blob_bigger_than_arena_is_centered() {
+        let mut pos = vec2(0.3, 0.4);
+        let mut vel = vec2(0.2, 0.2);
+        bounce_off_walls(&mut pos, &mut vel, 1.2, HALF_EXTENT);
+        assert!(pos.abs_diff_eq(vec2(0.3, 0.0), 1e-6));
+        bounce_off_walls(&mut pos, &mut vel, 2.0, HALF_EXTENT);
+        assert_eq!(pos, Vec2::ZERO);
+    }
+}
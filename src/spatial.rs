@@ -0,0 +1,131 @@
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+pub const GRID_CELL_SIZE: f32 = 0.25;
+
+/// Uniform grid over the play field for finding blobs near a point without scanning them all.
+/// Each blob is listed in every cell its bounding box touches, in the order it was added, so
+/// lookups visit blobs in the same order as a scan over the same iterator.
+#[derive(Resource, Clone, Debug)]
+pub struct BlobGrid {
+    cell_size: f32,
+    blobs: Vec<Entity>,
+    indices: HashMap<Entity, u32>,
+    cells: HashMap<IVec2, Vec<u32>>,
+}
+
+impl Default for BlobGrid {
+    fn default() -> Self {
+        Self::new(GRID_CELL_SIZE)
+    }
+}
+
+impl BlobGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            blobs: Vec::new(),
+            indices: HashMap::default(),
+            cells: HashMap::default(),
+        }
+    }
+
+    /// `blobs` as (entity, position, radius). Lookups only find blobs within the radius they
+    /// were added with, call `grow` when one gets bigger before the next rebuild.
+    pub fn rebuild(&mut self, blobs: impl IntoIterator<Item = (Entity, Vec2, f32)>) {
+        self.blobs.clear();
+        self.indices.clear();
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        for (entity, pos, radius) in blobs {
+            if radius <= 0.0 {
+                continue;
+            }
+            let index = self.blobs.len() as u32;
+            self.blobs.push(entity);
+            self.indices.insert(entity, index);
+            let (min, max) = self.cell_range(pos, radius);
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    self.cells.entry(ivec2(x, y)).or_default().push(index);
+                }
+            }
+        }
+        // Drop cells nothing has been in for a rebuild so the map doesn't fill up with empty
+        // cells as blobs drift around.
+        self.cells.retain(|_, cell| !cell.is_empty());
+    }
+
+    /// Lists a blob in the extra cells it covers at its new radius. It keeps its place in the
+    /// order, blobs that weren't in the last rebuild are ignored.
+    pub fn grow(&mut self, entity: Entity, pos: Vec2, radius: f32) {
+        let Some(&index) = self.indices.get(&entity) else {
+            return;
+        };
+        let (min, max) = self.cell_range(pos, radius);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let cell = self.cells.entry(ivec2(x, y)).or_default();
+                if let Err(at) = cell.binary_search(&index) {
+                    cell.insert(at, index);
+                }
+            }
+        }
+    }
+
+    /// Blobs that may contain `point`.
+    pub fn at_point(&self, point: Vec2) -> impl Iterator<Item = Entity> + '_ {
+        self.cells
+            .get(&self.cell(point))
+            .into_iter()
+            .flatten()
+            .map(|&index| self.blobs[index as usize])
+    }
+
+    /// Blobs that may overlap the circle, in the order they were added.
+    pub fn in_circle(&self, center: Vec2, radius: f32) -> Vec<Entity> {
+        let (min, max) = self.cell_range(center, radius);
+        let mut indices = Vec::new();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                if let Some(cell) = self.cells.get(&ivec2(x, y)) {
+                    indices.extend_from_slice(cell);
+                }
+            }
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+            .into_iter()
+            .map(|index| self.blobs[index as usize])
+            .collect()
+    }
+
+    fn cell(&self, pos: Vec2) -> IVec2 {
+        (pos / self.cell_size).floor().as_ivec2()
+    }
+
+    fn cell_range(&self, center: Vec2, radius: f32) -> (IVec2, IVec2) {
+        (self.cell(center - radius), self.cell(center + radius))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grown_blob_is_found_in_order() {
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+        let mut grid = BlobGrid::default();
+        grid.rebuild([(a, vec2(0.0, 0.0), 0.1), (b, vec2(0.6, 0.1), 0.05)]);
+        let point = vec2(0.4, 0.1);
+        assert_eq!(grid.at_point(point).count(), 0);
+
+        grid.grow(b, vec2(0.6, 0.1), 0.3);
+        grid.grow(a, vec2(0.0, 0.0), 0.5);
+        assert_eq!(grid.at_point(point).collect::<Vec<_>>(), vec![a, b]);
+    }
+}
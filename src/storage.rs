@@ -0,0 +1,113 @@
+//! Small key/value persistence. Files under the user's data directory on native, `localStorage`
+//! on wasm.
+
+use bevy::log::warn;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path(key: &str) -> Option<std::path::PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("glorby").join(format!("{key}.ron")))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(path(key)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, value: &str) {
+    let Some(path) = path(key) else {
+        warn!("No data directory, not saving {key}");
+        return;
+    };
+    if let Some(dir) = path.parent()
+        && let Err(e) = std::fs::create_dir_all(dir)
+    {
+        warn!("Failed to create {}: {e}", dir.display());
+        return;
+    }
+    if let Err(e) = std::fs::write(&path, value) {
+        warn!("Failed to write {}: {e}", path.display());
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?.get_item(&format!("glorby_{key}")).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, value: &str) {
+    let Some(storage) = local_storage() else {
+        warn!("No localStorage, not saving {key}");
+        return;
+    };
+    if storage.set_item(&format!("glorby_{key}"), value).is_err() {
+        warn!("Failed to write {key} to localStorage");
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_days_now() -> i64 {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    (secs / 86400) as i64
+}
+
+#[cfg(target_arch = "wasm32")]
+fn unix_days_now() -> i64 {
+    (js_sys::Date::now() / 86_400_000.0).floor() as i64
+}
+
+/// Today's UTC date as (year, month, day).
+pub fn today() -> (i32, u32, u32) {
+    civil_from_days(unix_days_now())
+}
+
+/// Today's UTC date as `YYYY-MM-DD`.
+pub fn today_string() -> String {
+    date_string(today())
+}
+
+/// (year, month, day) as `YYYY-MM-DD`.
+pub fn date_string((y, m, d): (i32, u32, u32)) -> String {
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+// https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y as i32, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11017), (2000, 3, 1));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn date_string_is_zero_padded() {
+        assert_eq!(date_string((2024, 2, 9)), "2024-02-09");
+    }
+}
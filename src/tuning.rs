@@ -0,0 +1,372 @@
+use std::fmt;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::sim::BlobKind;
+
+pub const TUNING_PATH: &str = "game.tuning.ron";
+
+/// Loads `GameTuning` from `assets/game.tuning.ron` and copies it into the `GameTuning`
+/// resource whenever the file changes. Debug builds with the default `hot_reload` feature pick
+/// up edits while the game runs.
+pub struct GlorbyTuningPlugin;
+
+impl Plugin for GlorbyTuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<GameTuning>()
+            .init_asset_loader::<GameTuningLoader>()
+            .add_systems(Startup, load_tuning)
+            .add_systems(PreUpdate, apply_tuning);
+    }
+}
+
+/// Gameplay balance values. Missing fields in the file fall back to these defaults.
+#[derive(Asset, Resource, TypePath, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameTuning {
+    /// Width / height of the play field, the same for every window. Applied when a game starts.
+    pub arena_aspect: f32,
+    pub init_count: u32,
+    pub init_size_min: f32,
+    pub init_size_range: f32,
+    /// Blobs smaller than this that aren't growing can be clicked.
+    pub clickable_size: f32,
+    pub shrink_speed: f32,
+    pub grow_speed: f32,
+    pub grow_seconds: f32,
+    pub splash_start_size: f32,
+    pub start_speed: f32,
+    /// Game speed gained per second, divided by the current speed.
+    pub speed_ramp: f32,
+    /// Splashes spawned when a splash hits a blob.
+    pub splash_splashes: DifficultyCurve,
+    /// Splashes spawned when a blob is clicked.
+    pub click_splashes: DifficultyCurve,
+    pub waves: WaveTuning,
+    pub kinds: BlobKindTuning,
+    pub physics: PhysicsTuning,
+    pub merge: MergeTuning,
+}
+
+impl Default for GameTuning {
+    fn default() -> Self {
+        Self {
+            arena_aspect: 16.0 / 9.0,
+            init_count: 28,
+            init_size_min: 0.18,
+            init_size_range: 0.3,
+            clickable_size: 0.1,
+            shrink_speed: 0.02,
+            grow_speed: 0.95,
+            grow_seconds: 0.5,
+            splash_start_size: 0.03,
+            start_speed: 0.8,
+            speed_ramp: 0.05,
+            splash_splashes: DifficultyCurve::new(vec![(5, 3), (13, 2)], 1),
+            click_splashes: DifficultyCurve::new(vec![(6, 6), (14, 4)], 3),
+            waves: WaveTuning::default(),
+            kinds: BlobKindTuning::default(),
+            physics: PhysicsTuning::default(),
+            merge: MergeTuning::default(),
+        }
+    }
+}
+
+impl GameTuning {
+    pub fn from_ron(bytes: &[u8]) -> Result<GameTuning, ron::error::SpannedError> {
+        ron::de::from_bytes(bytes)
+    }
+}
+
+/// Splash count by live blob count, as (live blob threshold, splash count) tiers. Tiers are kept
+/// sorted by threshold, and the tightest tier whose threshold is above the live blob count wins.
+/// `default` is used when there are at least as many live blobs as every threshold.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "UnsortedDifficultyCurve")]
+pub struct DifficultyCurve {
+    tiers: Vec<(u32, u32)>,
+    default: u32,
+}
+
+#[derive(Deserialize)]
+struct UnsortedDifficultyCurve {
+    tiers: Vec<(u32, u32)>,
+    default: u32,
+}
+
+impl From<UnsortedDifficultyCurve> for DifficultyCurve {
+    fn from(curve: UnsortedDifficultyCurve) -> Self {
+        DifficultyCurve::new(curve.tiers, curve.default)
+    }
+}
+
+impl DifficultyCurve {
+    /// Sorts the tiers, for duplicate thresholds the first one listed is kept.
+    pub fn new(mut tiers: Vec<(u32, u32)>, default: u32) -> Self {
+        tiers.sort_by_key(|(threshold, _)| *threshold);
+        tiers.dedup_by_key(|(threshold, _)| *threshold);
+        Self { tiers, default }
+    }
+
+    pub fn tiers(&self) -> &[(u32, u32)] {
+        &self.tiers
+    }
+
+    pub fn count(&self, live_blobs: u32) -> u32 {
+        self.tiers
+            .iter()
+            .find(|(threshold, _)| live_blobs < *threshold)
+            .map_or(self.default, |(_, count)| *count)
+    }
+}
+
+/// Blob groups that join a game in progress. The blobs a game starts with are wave 1, `waves`
+/// lists wave 2 onwards. Once the list runs out no more blobs come.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaveTuning {
+    /// Seconds from one wave to the next.
+    pub interval: f32,
+    /// The next wave comes early when fewer blobs than this are alive.
+    pub live_threshold: u32,
+    /// Raw points for still having blobs alive when the next wave is due.
+    pub clear_bonus: f32,
+    pub waves: Vec<Wave>,
+}
+
+impl Default for WaveTuning {
+    fn default() -> Self {
+        Self {
+            interval: 20.0,
+            live_threshold: 4,
+            clear_bonus: 50.0,
+            waves: vec![
+                Wave {
+                    count: 6,
+                    size_min: 0.15,
+                    size_range: 0.2,
+                    speed: 1.0,
+                    palette: vec![(0.2, 0.5, 0.7), (0.3, 0.6, 0.6)],
+                },
+                Wave {
+                    count: 8,
+                    size_min: 0.12,
+                    size_range: 0.2,
+                    speed: 1.2,
+                    palette: vec![(0.7, 0.3, 0.3), (0.7, 0.5, 0.2)],
+                },
+                Wave {
+                    count: 10,
+                    size_min: 0.1,
+                    size_range: 0.15,
+                    speed: 1.4,
+                    palette: vec![(0.6, 0.3, 0.7), (0.4, 0.2, 0.6)],
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Wave {
+    pub count: u32,
+    pub size_min: f32,
+    pub size_range: f32,
+    /// Multiplies the usual starting velocity.
+    pub speed: f32,
+    /// Colors picked at random for the wave's blobs, random colors when empty.
+    #[serde(default)]
+    pub palette: Vec<(f32, f32, f32)>,
+}
+
+/// How often special blobs show up and how they behave.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlobKindTuning {
+    /// Chance of each kind for every spawned blob, the rest are `Normal`.
+    pub chances: Vec<(BlobKind, f32)>,
+    /// Blobs reaching within this distance of a clicked bomb's center are splashed too.
+    pub bomb_radius: f32,
+    /// Blobs this far from a leech's center are drained.
+    pub leech_radius: f32,
+    /// Radius drained per second from each blob in reach, the leech gains half of it.
+    pub leech_drain: f32,
+}
+
+impl Default for BlobKindTuning {
+    fn default() -> Self {
+        Self {
+            chances: vec![
+                (BlobKind::Bomb, 0.06),
+                (BlobKind::Armored, 0.08),
+                (BlobKind::Splitter, 0.06),
+                (BlobKind::Leech, 0.04),
+            ],
+            bomb_radius: 0.3,
+            leech_radius: 0.35,
+            leech_drain: 0.01,
+        }
+    }
+}
+
+impl BlobKindTuning {
+    /// `roll` in [0, 1].
+    pub fn pick(&self, roll: f32) -> BlobKind {
+        let mut total = 0.0;
+        for (kind, chance) in &self.chances {
+            total += chance;
+            if roll < total {
+                return *kind;
+            }
+        }
+        BlobKind::Normal
+    }
+}
+
+/// Blob to blob collisions. When disabled blobs pass through each other.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhysicsTuning {
+    pub enabled: bool,
+    /// 1.0 bounces apart without losing energy, 0.0 moves on together.
+    pub restitution: f32,
+}
+
+impl Default for PhysicsTuning {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            restitution: 0.9,
+        }
+    }
+}
+
+/// Overlapping blobs combining into one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MergeTuning {
+    pub enabled: bool,
+    /// Overlap needed to merge, as a fraction of the smaller blob's diameter.
+    pub overlap: f32,
+}
+
+impl Default for MergeTuning {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            overlap: 0.5,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct GameTuningHandle(Handle<GameTuning>);
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameTuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+fn apply_tuning(
+    mut events: EventReader<AssetEvent<GameTuning>>,
+    handle: Res<GameTuningHandle>,
+    assets: Res<Assets<GameTuning>>,
+    mut tuning: ResMut<GameTuning>,
+) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event
+            && *id == handle.0.id()
+            && let Some(loaded) = assets.get(*id)
+        {
+            info!("Applied {TUNING_PATH}");
+            *tuning = loaded.clone();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GameTuningLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for GameTuningLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameTuningLoaderError::Io(e) => write!(f, "could not read tuning file: {e}"),
+            GameTuningLoaderError::Ron(e) => write!(f, "could not parse tuning file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for GameTuningLoaderError {}
+
+impl From<std::io::Error> for GameTuningLoaderError {
+    fn from(e: std::io::Error) -> Self {
+        GameTuningLoaderError::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for GameTuningLoaderError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        GameTuningLoaderError::Ron(e)
+    }
+}
+
+#[derive(Default)]
+struct GameTuningLoader;
+
+impl AssetLoader for GameTuningLoader {
+    type Asset = GameTuning;
+    type Settings = ();
+    type Error = GameTuningLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<GameTuning, GameTuningLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(GameTuning::from_ron(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curve_sorts_tiers() {
+        let curve = DifficultyCurve::new(vec![(13, 2), (5, 3)], 1);
+        assert_eq!(curve.tiers(), &[(5, 3), (13, 2)]);
+    }
+
+    #[test]
+    fn every_tier_is_reachable() {
+        let curve = DifficultyCurve::new(vec![(13, 2), (5, 3)], 1);
+        assert_eq!(curve.count(4), 3);
+        assert_eq!(curve.count(12), 2);
+        assert_eq!(curve.count(13), 1);
+    }
+
+    #[test]
+    fn deserialized_curve_is_sorted() {
+        let curve: DifficultyCurve =
+            ron::de::from_str("(tiers: [(13, 2), (5, 3)], default: 1)").unwrap();
+        assert_eq!(curve, DifficultyCurve::new(vec![(5, 3), (13, 2)], 1));
+    }
+
+    #[test]
+    fn duplicate_threshold_keeps_first() {
+        let curve = DifficultyCurve::new(vec![(8, 4), (5, 3), (8, 2)], 1);
+        assert_eq!(curve.tiers(), &[(5, 3), (8, 4)]);
+    }
+}
@@ -0,0 +1,341 @@
+use bevy::prelude::*;
+
+use crate::actions::{self, Action, ActionMap, Actions, Binding};
+use crate::combo::Combos;
+use crate::highscores::HighScores;
+use crate::input::CursorSettings;
+use crate::mode::GameMode;
+use crate::scoring::ScoringRules;
+use crate::sim::{GameState, LiveBlobs, Score, WaveState};
+use crate::storage;
+use crate::tuning::GameTuning;
+
+pub struct GlorbyUiPlugin;
+
+impl Plugin for GlorbyUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BindingsScreen>()
+            .add_systems(Startup, setup_ui)
+            .add_systems(Update, unpaused.run_if(in_state(GameState::Running)))
+            .add_systems(Update, update_game_text)
+            .add_systems(
+                Update,
+                (
+                    main_menu_paused.run_if(not(bindings_screen_open)),
+                    bindings_screen.run_if(bindings_screen_open),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Paused)),
+            )
+            .add_systems(
+                Update,
+                game_over_screen.run_if(in_state(GameState::GameOver)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct GameText;
+
+/// Rebinding screen shown from the pause menu.
+#[derive(Resource, Default)]
+struct BindingsScreen {
+    open: bool,
+    /// Index into `Action::ALL`.
+    selected: usize,
+    /// Waiting for a key or button to add to the selected action.
+    capturing: bool,
+}
+
+fn bindings_screen_open(screen: Res<BindingsScreen>) -> bool {
+    screen.open
+}
+
+#[derive(Component)]
+struct CenteredText;
+
+fn setup_ui(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Vh(2.0),
+            left: Val::Vh(2.0),
+            ..default()
+        },
+        GameText,
+    ));
+
+    commands
+        .spawn((Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            position_type: PositionType::Absolute,
+            ..default()
+        },))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::default(),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Node::default(),
+                CenteredText,
+            ));
+        });
+}
+
+fn update_game_text(
+    mut text: Single<&mut Text, With<GameText>>,
+    score: Res<Score>,
+    rules: Res<ScoringRules>,
+    combos: Res<Combos>,
+    live_blobs: Res<LiveBlobs>,
+    waves: Res<WaveState>,
+    tuning: Res<GameTuning>,
+    //game_speed: Res<GameSpeed>,
+) {
+    text.clear();
+    if score.mode.has_waves() && !tuning.waves.waves.is_empty() {
+        text.push_str(&format!("Wave  {}   ", waves.number));
+    }
+    text.push_str(&format!("Alive  {}", live_blobs.0));
+    if let Some(chain) = combos.current().filter(|chain| chain.reached > 1) {
+        text.push_str(&format!(
+            "   Combo {} x{:0.1}",
+            chain.reached,
+            rules.combo_multiplier(chain.reached)
+        ));
+    }
+    text.push('\n');
+    if score.mode.is_scored() {
+        text.push_str(&format!("Score  {:0.1}\n", score.composite(&rules)));
+    }
+    if let Some(time_left) = score.time_left() {
+        text.push_str(&format!("Time   {:0.0}\n", time_left.ceil()));
+    }
+    //text.push_str(&format!("  Hit  {}\n", score.hits));
+    //text.push_str(&format!(" Miss  {}\n", score.misses));
+    //text.push_str(&format!("Speed  {:0.1}\n", **game_speed));
+}
+
+fn main_menu_paused(
+    actions: Actions,
+    mut bindings_screen: ResMut<BindingsScreen>,
+    mut text: Single<&mut Text, With<CenteredText>>,
+    score: Res<Score>,
+    waves: Res<WaveState>,
+    high_scores: Option<Res<HighScores>>,
+    mut mode: ResMut<GameMode>,
+    mut cursor_settings: ResMut<CursorSettings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for key_mode in GameMode::ALL {
+        if actions.just_pressed(Action::SelectMode(key_mode)) {
+            *mode = key_mode;
+        }
+    }
+
+    if actions.just_pressed(Action::ToggleSnap) {
+        cursor_settings.snap = !cursor_settings.snap;
+        cursor_settings.save();
+    }
+
+    if actions.just_pressed(Action::Bindings) {
+        bindings_screen.open = true;
+        return;
+    }
+
+    // Wave 1 arrives with the first game, before that there is nothing to resume.
+    let game_started = waves.number > 0;
+    if game_started && actions.just_pressed(Action::Resume) {
+        next_state.set(GameState::Running);
+    }
+
+    if actions.just_pressed(Action::NewGame) {
+        next_state.set(GameState::Start);
+    }
+
+    text.clear();
+    text.push_str(&format!(
+        "PRESS {} TO START A NEW GAME\n\n",
+        actions.map.describe(Action::NewGame)
+    ));
+    if game_started {
+        text.push_str(&format!(
+            "PRESS {} TO RESUME {}\n",
+            actions.map.describe(Action::Resume),
+            score.mode.name()
+        ));
+    }
+
+    text.push_str("\nMODE ");
+    for key_mode in GameMode::ALL {
+        let key = actions.map.describe(Action::SelectMode(key_mode));
+        if key_mode == *mode {
+            text.push_str(&format!("  [{} {}]", key, key_mode.name()));
+        } else {
+            text.push_str(&format!("   {} {} ", key, key_mode.name()));
+        }
+    }
+    text.push('\n');
+    text.push_str(&format!(
+        "{}  SNAP GAMEPAD CURSOR TO BLOBS  {}\n",
+        actions.map.describe(Action::ToggleSnap),
+        if cursor_settings.snap { "ON" } else { "OFF" }
+    ));
+    text.push_str(&format!(
+        "{}  CHANGE BINDINGS\n",
+        actions.map.describe(Action::Bindings)
+    ));
+
+    if mode.is_daily() {
+        let best = high_scores
+            .as_ref()
+            .and_then(|h| h.daily_best(&storage::today_string()));
+        match best {
+            Some(best) => text.push_str(&format!("TODAY'S BEST  {:0.1}\n", best.score)),
+            None => text.push_str("NO DAILY RUN YET TODAY\n"),
+        }
+    }
+
+    if let Some(high_scores) = high_scores
+        && high_scores.for_mode(*mode).next().is_some()
+    {
+        text.push_str(&format!("\n{} HIGH SCORES\n", mode.name()));
+        for (i, entry) in high_scores.for_mode(*mode).enumerate() {
+            text.push_str(&format!(
+                "{:>2}  {:>8.1}  {:>4} HIT {:>4} MISS  {:>4.0}s  {}\n",
+                i + 1,
+                entry.score,
+                entry.hits,
+                entry.misses,
+                entry.seconds,
+                entry.date
+            ));
+        }
+    }
+}
+
+/// Menu navigation uses fixed keys and buttons so broken bindings can always be fixed here.
+fn bindings_screen(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut action_map: ResMut<ActionMap>,
+    mut screen: ResMut<BindingsScreen>,
+    mut text: Single<&mut Text, With<CenteredText>>,
+) {
+    let selected = Action::ALL[screen.selected];
+    let pad_just_pressed =
+        |button: GamepadButton| gamepads.iter().any(|gamepad| gamepad.just_pressed(button));
+
+    if screen.capturing {
+        if keyboard_input.just_pressed(KeyCode::Escape) {
+            screen.capturing = false;
+        } else if let Some(binding) =
+            actions::any_just_pressed(&keyboard_input, &mouse_buttons, gamepads.iter())
+        {
+            action_map.bind(selected, binding);
+            action_map.save();
+            screen.capturing = false;
+        }
+    } else if keyboard_input.just_pressed(KeyCode::Escape) || pad_just_pressed(GamepadButton::East)
+    {
+        screen.open = false;
+        return;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowUp)
+        || pad_just_pressed(GamepadButton::DPadUp)
+    {
+        screen.selected = (screen.selected + Action::ALL.len() - 1) % Action::ALL.len();
+    } else if keyboard_input.just_pressed(KeyCode::ArrowDown)
+        || pad_just_pressed(GamepadButton::DPadDown)
+    {
+        screen.selected = (screen.selected + 1) % Action::ALL.len();
+    } else if keyboard_input.just_pressed(KeyCode::Enter) || pad_just_pressed(GamepadButton::South)
+    {
+        screen.capturing = true;
+    } else if keyboard_input.just_pressed(KeyCode::Backspace)
+        || pad_just_pressed(GamepadButton::West)
+    {
+        action_map.clear(selected);
+        action_map.save();
+    } else if keyboard_input.just_pressed(KeyCode::KeyR) {
+        *action_map = ActionMap::default();
+        action_map.save();
+    }
+
+    text.clear();
+    text.push_str("BINDINGS\n\n");
+    for (i, action) in Action::ALL.into_iter().enumerate() {
+        let marker = if i == screen.selected { ">" } else { " " };
+        let bindings = action_map
+            .bindings(action)
+            .iter()
+            .map(Binding::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        text.push_str(&format!("{marker} {:<14} {bindings}\n", action.name()));
+    }
+    text.push('\n');
+    if screen.capturing {
+        text.push_str(&format!(
+            "PRESS A KEY OR BUTTON FOR {}, ESCAPE TO CANCEL\n",
+            selected.name()
+        ));
+    } else {
+        text.push_str("UP/DOWN SELECT   ENTER ADD   BACKSPACE CLEAR   R RESET ALL   ESCAPE BACK\n");
+    }
+}
+
+fn game_over_screen(
+    actions: Actions,
+    mut text: Single<&mut Text, With<CenteredText>>,
+    score: Res<Score>,
+    rules: Res<ScoringRules>,
+    waves: Res<WaveState>,
+    tuning: Res<GameTuning>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(Action::NewGame) {
+        next_state.set(GameState::Start);
+    }
+
+    text.clear();
+    if score.time_left() == Some(0.0) {
+        text.push_str("TIME UP\n\n");
+    } else {
+        text.push_str("GAME OVER\n\n");
+    }
+    text.push_str(&format!("SCORE  {:0.1}\n", score.composite(&rules)));
+    text.push_str(&format!("HITS  {}\n", score.hits));
+    text.push_str(&format!("MISSES  {}\n", score.misses));
+    text.push_str(&format!("ACCURACY  {:0.0}%\n", score.accuracy() * 100.0));
+    text.push_str(&format!(
+        "BEST CHAIN  {} BLOBS  DEPTH {}  {:0.1}s\n",
+        score.best_chain.reached, score.best_chain.depth, score.best_chain.duration
+    ));
+    if score.mode.has_waves() && !tuning.waves.waves.is_empty() {
+        text.push_str(&format!(
+            "WAVES  REACHED {}  CLEARED {}\n",
+            waves.number, waves.cleared
+        ));
+    }
+    text.push_str(&format!("TIME SURVIVED  {:0.1}s\n", score.time));
+    text.push_str(&format!("PEAK SPEED  {:0.2}\n", score.peak_speed));
+    text.push_str(&format!(
+        "\nPRESS {} TO START A NEW GAME\n",
+        actions.map.describe(Action::NewGame)
+    ));
+}
+
+fn unpaused(
+    actions: Actions,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut text: Single<&mut Text, With<CenteredText>>,
+) {
+    if actions.just_pressed(Action::Pause) {
+        next_state.set(GameState::Paused);
+    }
+    text.clear();
+}